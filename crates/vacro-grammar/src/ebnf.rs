@@ -0,0 +1,172 @@
+use vacro_doc_i18n::doc_i18n;
+
+use crate::{Grammar, Node, Variant};
+
+#[doc_i18n]
+impl Grammar {
+    /// @cn 渲染为 W3C 风格的 EBNF，每条规则占一行：首条规则为结构体本身，
+    /// @cn 其后是其中引用的多态枚举 (e.g. `Signature ::= 'fn' Ident '(' ... ')'`)
+    /// @en Renders the grammar as W3C-style EBNF, one rule per line: the struct itself first,
+    /// @en followed by the polymorphic enums it references (e.g. `Signature ::= 'fn' Ident '(' ... ')'`)
+    pub fn to_ebnf(&self) -> String {
+        let mut ebnf = Ebnf { rules: vec![] };
+        let body = ebnf.sequence(&self.items);
+        ebnf.rules.insert(0, (self.name.to_string(), body));
+        ebnf.rules
+            .iter()
+            .map(|(name, body)| format!("{name} ::= {body}").trim_end().to_string())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// 单个表达式的 EBNF，供铁路图中的注释使用
+pub(crate) fn expression(items: &[Node]) -> String {
+    Ebnf { rules: vec![] }.sequence(items)
+}
+
+struct Ebnf {
+    /// 多态枚举生成的规则，按首次出现的顺序排列
+    rules: Vec<(String, String)>,
+}
+
+impl Ebnf {
+    fn sequence(&mut self, items: &[Node]) -> String {
+        items
+            .iter()
+            .map(|item| self.node(item))
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// 作为后缀运算符 (`?`、`*`、`+`) 的操作数，必要时加上括号
+    fn operand(&mut self, items: &[Node]) -> String {
+        match items {
+            [item] if atomic(item) => self.node(item),
+            items => format!("( {} )", self.sequence(items)),
+        }
+    }
+
+    fn node(&mut self, node: &Node) -> String {
+        match node {
+            Node::Literal { text } => quote(text),
+            Node::Capture { ty, .. } => match token_literal(ty) {
+                Some(token) => quote(token),
+                None => ty.to_string(),
+            },
+            Node::Nested { items, .. } => self.sequence(items),
+            Node::Group { delimiter, items } => {
+                let (open, close) = delimiter.pair();
+                match self.sequence(items) {
+                    inner if inner.is_empty() => format!("{} {}", quote(open), quote(close)),
+                    inner => format!("{} {inner} {}", quote(open), quote(close)),
+                }
+            }
+            Node::Optional { items } => format!("{}?", self.operand(items)),
+            Node::Repeat {
+                min,
+                max,
+                separator,
+                items,
+            } => {
+                let item = self.operand(items);
+                let Some(separator) = separator else {
+                    return format!("{item}{}", suffix(*min, *max));
+                };
+                // `x, x, x` => `x ( ',' x )*`
+                let rest = match max.map(|max| max.saturating_sub(1)) {
+                    Some(0) => String::new(),
+                    rest => format!(
+                        " ( {} {item} ){}",
+                        quote(separator),
+                        suffix(min.saturating_sub(1), rest)
+                    ),
+                };
+                match min {
+                    0 => format!("( {item}{rest} )?"),
+                    _ => format!("{item}{rest}"),
+                }
+            }
+            Node::Lookahead { negative, items } => {
+                let op = if *negative { "!" } else { "&" };
+                format!("{op}{}", self.operand(items))
+            }
+            Node::Choice {
+                ty: Some(ty),
+                variants,
+                ..
+            } => {
+                if !self.rules.iter().any(|(name, _)| name == ty) {
+                    // 先占位，避免递归引用时重复生成
+                    let index = self.rules.len();
+                    self.rules.push((ty.to_string(), String::new()));
+                    self.rules[index].1 = self.alternatives(variants);
+                }
+                ty.to_string()
+            }
+            Node::Choice { variants, .. } => format!("( {} )", self.alternatives(variants)),
+            Node::Unordered { separator, members } => {
+                let member = members
+                    .iter()
+                    .map(|member| {
+                        let key = quote(&member.key);
+                        match self.sequence(&member.items) {
+                            inner if inner.is_empty() => key,
+                            inner => format!("{key} {inner}"),
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" | ");
+                let body = format!("( {member} ) ( {} ( {member} ) )*", quote(separator));
+                if members.iter().all(|member| member.optional) {
+                    format!("/* any order */ ( {body} )?")
+                } else {
+                    format!("/* any order */ {body}")
+                }
+            }
+        }
+    }
+
+    fn alternatives(&mut self, variants: &[Variant]) -> String {
+        variants
+            .iter()
+            .map(|variant| self.sequence(&variant.items))
+            .collect::<Vec<_>>()
+            .join(" | ")
+    }
+}
+
+/// 渲染结果不需要括号即可接后缀运算符的节点
+fn atomic(node: &Node) -> bool {
+    match node {
+        Node::Literal { .. } | Node::Capture { .. } | Node::Choice { ty: Some(_), .. } => true,
+        Node::Nested { items, .. } => matches!(&items[..], [item] if atomic(item)),
+        _ => false,
+    }
+}
+
+/// `Token![;]` 之类的捕获以字面量的形式展示
+pub(crate) fn token_literal(ty: &str) -> Option<&str> {
+    ty.strip_prefix("Token![")?.strip_suffix(']')
+}
+
+fn quote(text: &str) -> String {
+    if text.contains('\'') {
+        format!("\"{text}\"")
+    } else {
+        format!("'{text}'")
+    }
+}
+
+pub(crate) fn suffix(min: usize, max: Option<usize>) -> String {
+    match (min, max) {
+        (0, None) => String::from("*"),
+        (1, None) => String::from("+"),
+        (0, Some(1)) => String::from("?"),
+        (1, Some(1)) => String::new(),
+        (min, None) => format!("{{{min},}}"),
+        (min, Some(max)) if min == max => format!("{{{min}}}"),
+        (min, Some(max)) => format!("{{{min},{max}}}"),
+    }
+}
@@ -0,0 +1,282 @@
+#![warn(missing_docs)]
+//! <div class="doc-cn">
+//!
+//! `vacro-parser` 的 `define!` 生成的语法描述，可通过 `Name::grammar()` 在运行时获取，
+//! 用于在错误信息、文档或 IDE 工具中展示用法；
+//! 也可以渲染为 EBNF 文本 ([`Grammar::to_ebnf`]) 或 SVG 铁路图 ([`Grammar::to_railroad_svg`])
+//!
+//! </div>
+//!
+//! <div class="doc-en">
+//!
+//! Grammar descriptions generated by `vacro-parser`'s `define!`, available at runtime via
+//! `Name::grammar()` for rendering usage in error messages, docs or IDE tooling;
+//! they can also be rendered as EBNF text ([`Grammar::to_ebnf`]) or an SVG railroad diagram
+//! ([`Grammar::to_railroad_svg`])
+//!
+//! </div>
+use std::{borrow::Cow, fmt};
+
+use vacro_doc_i18n::doc_i18n;
+
+mod ebnf;
+mod railroad;
+
+#[doc_i18n]
+/// @cn `define!` 结构体的语法，`Display` 输出单行的用法描述 (e.g. `fn <Ident> ( <FnArg>, ... )`)
+/// @en The grammar of a `define!` struct; `Display` renders a one-line usage (e.g. `fn <Ident> ( <FnArg>, ... )`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Grammar {
+    /// @cn 结构体名称
+    /// @en Name of the struct
+    pub name: Cow<'static, str>,
+    /// @cn 依次匹配的语法节点
+    /// @en Grammar nodes matched in order
+    pub items: Cow<'static, [Node]>,
+}
+
+#[doc_i18n]
+/// @cn 语法节点
+/// @en A grammar node
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "kind", rename_all = "snake_case"))]
+pub enum Node {
+    /// @cn 字面量 (e.g. `fn`, `->`)
+    /// @en A literal token (e.g. `fn`, `->`)
+    Literal {
+        /// @cn 字面量的内容
+        /// @en The literal text
+        text: Cow<'static, str>,
+    },
+    /// @cn 捕获一个类型，行内与匿名捕获没有名称
+    /// @en A captured type; inline and anonymous captures have no name
+    Capture {
+        /// @cn 字段名
+        /// @en Field name
+        name: Option<Cow<'static, str>>,
+        /// @cn 捕获的类型 (e.g. `Ident`)
+        /// @en Captured type (e.g. `Ident`)
+        ty: Cow<'static, str>,
+        /// @cn 捕获上的文档注释 (e.g. `#(#[doc = "函数名"] name: Ident)`)
+        /// @en Doc comment on the capture (e.g. `#(#[doc = "function name"] name: Ident)`)
+        doc: Option<Cow<'static, str>>,
+    },
+    /// @cn 具名的嵌套捕获或 `span(...)`、`tokens(...)` 记录
+    /// @en A named nested capture, or a `span(...)` / `tokens(...)` record
+    Nested {
+        /// @cn 字段名
+        /// @en Field name
+        name: Option<Cow<'static, str>>,
+        /// @cn 捕获上的文档注释
+        /// @en Doc comment on the capture
+        doc: Option<Cow<'static, str>>,
+        /// @cn 子节点
+        /// @en Child nodes
+        items: Cow<'static, [Node]>,
+    },
+    /// @cn 分组 `(...)`、`[...]`、`{...}`
+    /// @en A delimited group `(...)`, `[...]` or `{...}`
+    Group {
+        /// @cn 分隔符
+        /// @en Delimiter of the group
+        delimiter: Delimiter,
+        /// @cn 分组中的节点
+        /// @en Nodes inside the group
+        items: Cow<'static, [Node]>,
+    },
+    /// @cn 可选 (`?`)
+    /// @en Optional (`?`)
+    Optional {
+        /// @cn 可以缺省的节点
+        /// @en Nodes that may be absent
+        items: Cow<'static, [Node]>,
+    },
+    /// @cn 重复 (`*`、`+`、`{n,m}`)
+    /// @en Repetition (`*`, `+`, `{n,m}`)
+    Repeat {
+        /// @cn 最少次数
+        /// @en Minimum count
+        min: usize,
+        /// @cn 最多次数，`None` 为不限
+        /// @en Maximum count, `None` if unbounded
+        max: Option<usize>,
+        /// @cn 分隔符 (e.g. `,`)
+        /// @en Separator (e.g. `,`)
+        separator: Option<Cow<'static, str>>,
+        /// @cn 每次重复匹配的节点
+        /// @en Nodes matched by each repetition
+        items: Cow<'static, [Node]>,
+    },
+    /// @cn 前瞻断言 (`&` / `!`)，不消费输入
+    /// @en Lookahead assertion (`&` / `!`), consumes no input
+    Lookahead {
+        /// @cn 是否为负向前瞻
+        /// @en Whether the assertion is negative
+        negative: bool,
+        /// @cn 断言的节点
+        /// @en Asserted nodes
+        items: Cow<'static, [Node]>,
+    },
+    /// @cn 多态枚举 (`Enum { ... }`) 或匿名选择 (`a | b`)，按顺序尝试各分支
+    /// @en A polymorphic enum (`Enum { ... }`) or an alternation (`a | b`), tried in order
+    Choice {
+        /// @cn 字段名
+        /// @en Field name
+        name: Option<Cow<'static, str>>,
+        /// @cn 枚举名，匿名选择为 `None`
+        /// @en Enum name, `None` for alternations
+        ty: Option<Cow<'static, str>>,
+        /// @cn 各个分支
+        /// @en The branches
+        variants: Cow<'static, [Variant]>,
+    },
+    /// @cn 无序组 (`&[sep]: ...`)，各成员可按任意顺序出现
+    /// @en An unordered group (`&[sep]: ...`) whose members may appear in any order
+    Unordered {
+        /// @cn 成员之间的分隔符
+        /// @en Separator between members
+        separator: Cow<'static, str>,
+        /// @cn 各个成员
+        /// @en The members
+        members: Cow<'static, [Member]>,
+    },
+}
+
+#[doc_i18n]
+/// @cn 多态枚举或选择的分支
+/// @en A branch of a polymorphic enum or an alternation
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Variant {
+    /// @cn 分支名，匿名选择依次为 `_0`、`_1`……
+    /// @en Variant name; alternation branches are `_0`, `_1`, ...
+    pub name: Cow<'static, str>,
+    /// @cn 分支匹配的节点
+    /// @en Nodes matched by the branch
+    pub items: Cow<'static, [Node]>,
+}
+
+#[doc_i18n]
+/// @cn 无序组的成员
+/// @en A member of an unordered group
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Member {
+    /// @cn 成员的键，即首个字面量
+    /// @en The member's key, its leading literal
+    pub key: Cow<'static, str>,
+    /// @cn 成员是否可以缺省
+    /// @en Whether the member may be absent
+    pub optional: bool,
+    /// @cn 键之后匹配的节点
+    /// @en Nodes matched after the key
+    pub items: Cow<'static, [Node]>,
+}
+
+#[doc_i18n]
+/// @cn 分组的分隔符
+/// @en Delimiter of a group
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Delimiter {
+    /// `( ... )`
+    Parenthesis,
+    /// `{ ... }`
+    Brace,
+    /// `[ ... ]`
+    Bracket,
+}
+
+impl Delimiter {
+    fn pair(self) -> (&'static str, &'static str) {
+        match self {
+            Delimiter::Parenthesis => ("(", ")"),
+            Delimiter::Brace => ("{", "}"),
+            Delimiter::Bracket => ("[", "]"),
+        }
+    }
+}
+
+impl fmt::Display for Grammar {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&describe(&self.items))
+    }
+}
+
+impl fmt::Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&describe(std::slice::from_ref(self)))
+    }
+}
+
+// 单行语法描述，`define!` 生成的报错也由此得出：`<Expr>, ...`、`<Stmt> ...+`、`-> <Type>?`
+fn describe(items: &[Node]) -> String {
+    items
+        .iter()
+        .map(|item| match item {
+            Node::Literal { text } => text.to_string(),
+            Node::Capture { ty, .. } => format!("<{ty}>"),
+            Node::Nested { items, .. } => describe(items),
+            Node::Group { delimiter, items } => {
+                let (open, close) = delimiter.pair();
+                match describe(items) {
+                    inner if inner.is_empty() => format!("{open}{close}"),
+                    inner => format!("{open} {inner} {close}"),
+                }
+            }
+            Node::Optional { items } => match describe(items) {
+                inner if inner.is_empty() => inner,
+                inner => format!("{inner}?"),
+            },
+            Node::Repeat {
+                min,
+                max,
+                separator,
+                items,
+            } => {
+                let inner = describe(items);
+                if inner.is_empty() {
+                    return inner;
+                }
+                let bounds = match (min, max) {
+                    (0, None) => String::new(),
+                    (1, None) => String::from("+"),
+                    (min, None) => format!("{{{min},}}"),
+                    (min, Some(max)) if min == max => format!("{{{min}}}"),
+                    (min, Some(max)) => format!("{{{min},{max}}}"),
+                };
+                format!(
+                    "{inner}{} ...{bounds}",
+                    separator.as_deref().unwrap_or_default()
+                )
+            }
+            Node::Lookahead { .. } => String::new(),
+            Node::Choice { ty: Some(ty), .. } => format!("<{ty}>"),
+            Node::Choice { variants, .. } => variants
+                .iter()
+                .map(|variant| describe(&variant.items))
+                .collect::<Vec<_>>()
+                .join(" | "),
+            Node::Unordered { separator, members } => members
+                .iter()
+                .map(|member| {
+                    let inner = match describe(&member.items) {
+                        inner if inner.is_empty() => member.key.to_string(),
+                        inner => format!("{} {inner}", member.key),
+                    };
+                    if member.optional {
+                        format!("{inner}?")
+                    } else {
+                        inner
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(&format!("{separator} ")),
+        })
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
@@ -0,0 +1,491 @@
+use std::borrow::Cow;
+
+use vacro_doc_i18n::doc_i18n;
+
+use crate::{
+    ebnf::{expression, suffix, token_literal},
+    Grammar, Node,
+};
+
+/// 转角半径
+const ARC: i32 = 10;
+/// 相邻节点之间连线的长度
+const GAP: i32 = 10;
+/// 上下相邻分支之间的间距
+const V_SPACE: i32 = 8;
+/// 方框高度的一半
+const BOX: i32 = 11;
+const CHAR_WIDTH: i32 = 8;
+const PADDING: i32 = 10;
+
+#[doc_i18n]
+impl Grammar {
+    /// @cn 渲染为 SVG 铁路图，线条与文字使用 `currentColor`，可以直接嵌入 HTML 或 rustdoc
+    /// @en Renders the grammar as an SVG railroad diagram; lines and text use `currentColor`,
+    /// @en so it can be embedded into HTML or rustdoc as is
+    pub fn to_railroad_svg(&self) -> String {
+        let diagram = Diagram::sequence(&self.items);
+        let size = diagram.size();
+        let (up, down) = (size.up.max(BOX), size.down.max(BOX));
+        let width = size.width + 2 * (GAP + PADDING);
+        let height = up + down + 2 * PADDING;
+        let y = PADDING + up;
+        let end = PADDING + GAP + size.width;
+
+        // 开头不换行、中间不留空行，保证在 Markdown 中被视为一个完整的 HTML 块
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" class=\"railroad-diagram\" \
+             width=\"{width}\" height=\"{height}\" viewBox=\"0 0 {width} {height}\">\n\
+             <g fill=\"none\" stroke=\"currentColor\" stroke-width=\"1.5\" \
+             font-family=\"monospace\" font-size=\"12\" text-anchor=\"middle\">\n"
+        );
+        path(
+            &mut svg,
+            format!("M {PADDING} {} v {} m 0 {BOX} h {GAP}", y - BOX, 2 * BOX),
+        );
+        diagram.render(&mut svg, PADDING + GAP, y);
+        path(
+            &mut svg,
+            format!("M {end} {y} h {GAP} m 0 {} v {}", -BOX, 2 * BOX),
+        );
+        svg.push_str("</g>\n</svg>");
+        svg
+    }
+}
+
+enum Diagram {
+    Terminal(String),
+    NonTerminal(String),
+    Comment(String),
+    /// 带有悬停提示的节点，提示来自捕获的文档注释
+    Titled {
+        title: String,
+        inner: Box<Diagram>,
+    },
+    Sequence(Vec<Diagram>),
+    /// 第一个分支位于主线上，其余分支依次排在下方
+    Choice(Vec<Diagram>),
+    /// 跳过的路线位于上方
+    Optional(Box<Diagram>),
+    /// 至少一次，回环位于下方，分隔符绘制在回环上
+    Repeat {
+        item: Box<Diagram>,
+        separator: Option<Box<Diagram>>,
+    },
+}
+
+struct Size {
+    width: i32,
+    /// 主线以上的高度
+    up: i32,
+    /// 主线以下的高度
+    down: i32,
+}
+
+impl Diagram {
+    fn sequence(items: &[Node]) -> Self {
+        Self::flatten(items.iter().map(Self::node).collect())
+    }
+
+    /// 展开嵌套的序列，单个元素的序列直接使用其元素
+    fn flatten(children: Vec<Diagram>) -> Self {
+        let mut flat = vec![];
+        for child in children {
+            match child {
+                Diagram::Sequence(inner) => flat.extend(inner),
+                child => flat.push(child),
+            }
+        }
+        match flat.len() {
+            1 => flat.remove(0),
+            _ => Diagram::Sequence(flat),
+        }
+    }
+
+    fn node(node: &Node) -> Self {
+        match node {
+            Node::Literal { text } => Diagram::Terminal(text.to_string()),
+            Node::Capture { ty, doc, .. } => Self::titled(
+                doc,
+                match token_literal(ty) {
+                    Some(token) => Diagram::Terminal(token.to_string()),
+                    None => Diagram::NonTerminal(ty.to_string()),
+                },
+            ),
+            Node::Nested { items, doc, .. } => Self::titled(doc, Self::sequence(items)),
+            Node::Group { delimiter, items } => {
+                let (open, close) = delimiter.pair();
+                Self::flatten(vec![
+                    Diagram::Terminal(open.to_string()),
+                    Self::sequence(items),
+                    Diagram::Terminal(close.to_string()),
+                ])
+            }
+            Node::Optional { items } => Diagram::Optional(Box::new(Self::sequence(items))),
+            Node::Repeat {
+                min,
+                max,
+                separator,
+                items,
+            } => {
+                let repeat = Diagram::Repeat {
+                    item: Box::new(Self::sequence(items)),
+                    separator: separator
+                        .as_ref()
+                        .map(|separator| Box::new(Diagram::Terminal(separator.to_string()))),
+                };
+                let repeat = match min {
+                    0 => Diagram::Optional(Box::new(repeat)),
+                    _ => repeat,
+                };
+                match (min, max) {
+                    (0 | 1, None) => repeat,
+                    (min, max) => {
+                        Diagram::Sequence(vec![repeat, Diagram::Comment(suffix(*min, *max))])
+                    }
+                }
+            }
+            Node::Lookahead { negative, items } => {
+                let op = if *negative { "not" } else { "followed by" };
+                Diagram::Comment(format!("{op} {}", expression(items)))
+            }
+            Node::Choice { variants, .. } => Diagram::Choice(
+                variants
+                    .iter()
+                    .map(|variant| Self::sequence(&variant.items))
+                    .collect(),
+            ),
+            Node::Unordered { separator, members } => {
+                let member = Diagram::Choice(
+                    members
+                        .iter()
+                        .map(|member| {
+                            Self::flatten(vec![
+                                Diagram::Terminal(member.key.to_string()),
+                                Self::sequence(&member.items),
+                            ])
+                        })
+                        .collect(),
+                );
+                let repeat = Diagram::Repeat {
+                    item: Box::new(member),
+                    separator: Some(Box::new(Diagram::Terminal(separator.to_string()))),
+                };
+                let body = if members.iter().all(|member| member.optional) {
+                    Diagram::Optional(Box::new(repeat))
+                } else {
+                    repeat
+                };
+                Diagram::Sequence(vec![Diagram::Comment(String::from("any order")), body])
+            }
+        }
+    }
+
+    fn titled(doc: &Option<Cow<'static, str>>, inner: Diagram) -> Self {
+        match doc {
+            // 文档中嵌入的 SVG 不能包含空行
+            Some(doc) => Diagram::Titled {
+                title: doc.split_whitespace().collect::<Vec<_>>().join(" "),
+                inner: Box::new(inner),
+            },
+            None => inner,
+        }
+    }
+
+    fn size(&self) -> Size {
+        match self {
+            Diagram::Titled { inner, .. } => inner.size(),
+            Diagram::Terminal(text) | Diagram::NonTerminal(text) | Diagram::Comment(text) => Size {
+                width: text_width(text) + 2 * GAP,
+                up: BOX,
+                down: BOX,
+            },
+            Diagram::Sequence(children) => {
+                let sizes = children.iter().map(Diagram::size).collect::<Vec<_>>();
+                Size {
+                    width: sizes.iter().map(|size| size.width).sum::<i32>()
+                        + GAP * (sizes.len() as i32 - 1).max(0),
+                    up: sizes.iter().map(|size| size.up).max().unwrap_or(0),
+                    down: sizes.iter().map(|size| size.down).max().unwrap_or(0),
+                }
+            }
+            Diagram::Choice(branches) => {
+                let sizes = branches.iter().map(Diagram::size).collect::<Vec<_>>();
+                let offsets = offsets(&sizes);
+                match (sizes.first(), sizes.last(), offsets.last()) {
+                    (Some(first), Some(last), Some(offset)) => Size {
+                        width: sizes.iter().map(|size| size.width).max().unwrap_or(0) + 4 * ARC,
+                        up: first.up,
+                        down: offset + last.down,
+                    },
+                    _ => Size {
+                        width: 0,
+                        up: 0,
+                        down: 0,
+                    },
+                }
+            }
+            Diagram::Optional(item) => {
+                let size = item.size();
+                Size {
+                    width: size.width + 4 * ARC,
+                    up: bypass(&size),
+                    down: size.down,
+                }
+            }
+            Diagram::Repeat { item, separator } => {
+                let size = item.size();
+                let separator = separator.as_ref().map(|separator| separator.size());
+                let width = size
+                    .width
+                    .max(separator.as_ref().map_or(0, |sep| sep.width));
+                let back = back(&size, separator.as_ref());
+                Size {
+                    width: width + 2 * ARC,
+                    up: size.up,
+                    down: back + separator.map_or(0, |sep| sep.down),
+                }
+            }
+        }
+    }
+
+    /// 在 (x, y) 处绘制，y 为主线所在的高度
+    fn render(&self, svg: &mut String, x: i32, y: i32) {
+        match self {
+            Diagram::Titled { title, inner } => {
+                svg.push_str(&format!("<g>\n<title>{}</title>\n", escape(title)));
+                inner.render(svg, x, y);
+                svg.push_str("</g>\n");
+            }
+            Diagram::Terminal(text) | Diagram::NonTerminal(text) => {
+                let width = self.size().width;
+                let rx = if matches!(self, Diagram::Terminal(_)) {
+                    BOX
+                } else {
+                    0
+                };
+                svg.push_str(&format!(
+                    "<rect x=\"{x}\" y=\"{}\" width=\"{width}\" height=\"{}\" rx=\"{rx}\"/>\n",
+                    y - BOX,
+                    2 * BOX
+                ));
+                text_element(svg, x + width / 2, y, text, "");
+            }
+            Diagram::Comment(text) => {
+                let width = self.size().width;
+                path(svg, format!("M {x} {y} h {GAP}"));
+                path(svg, format!("M {} {y} h {GAP}", x + width - GAP));
+                text_element(svg, x + width / 2, y, text, " font-style=\"italic\"");
+            }
+            Diagram::Sequence(children) => {
+                let mut cursor = x;
+                for (i, child) in children.iter().enumerate() {
+                    if i > 0 {
+                        path(svg, format!("M {cursor} {y} h {GAP}"));
+                        cursor += GAP;
+                    }
+                    child.render(svg, cursor, y);
+                    cursor += child.size().width;
+                }
+            }
+            Diagram::Choice(branches) => {
+                let sizes = branches.iter().map(Diagram::size).collect::<Vec<_>>();
+                let inner = sizes.iter().map(|size| size.width).max().unwrap_or(0);
+                for ((branch, size), offset) in branches.iter().zip(&sizes).zip(offsets(&sizes)) {
+                    let (start, end) = (x + 2 * ARC, x + 2 * ARC + size.width);
+                    if offset == 0 {
+                        path(svg, format!("M {x} {y} h {}", 2 * ARC));
+                        branch.render(svg, start, y);
+                        path(
+                            svg,
+                            format!("M {end} {y} h {}", inner - size.width + 2 * ARC),
+                        );
+                        continue;
+                    }
+                    let vertical = offset - 2 * ARC;
+                    path(
+                        svg,
+                        format!(
+                            "M {x} {y} a {ARC} {ARC} 0 0 1 {ARC} {ARC} v {vertical} \
+                             a {ARC} {ARC} 0 0 0 {ARC} {ARC}"
+                        ),
+                    );
+                    branch.render(svg, start, y + offset);
+                    path(
+                        svg,
+                        format!(
+                            "M {end} {} h {} a {ARC} {ARC} 0 0 0 {ARC} {} v {} \
+                             a {ARC} {ARC} 0 0 1 {ARC} {}",
+                            y + offset,
+                            inner - size.width,
+                            -ARC,
+                            -vertical,
+                            -ARC
+                        ),
+                    );
+                }
+            }
+            Diagram::Optional(item) => {
+                let size = item.size();
+                let vertical = bypass(&size) - 2 * ARC;
+                path(svg, format!("M {x} {y} h {}", 2 * ARC));
+                item.render(svg, x + 2 * ARC, y);
+                path(
+                    svg,
+                    format!("M {} {y} h {}", x + 2 * ARC + size.width, 2 * ARC),
+                );
+                path(
+                    svg,
+                    format!(
+                        "M {x} {y} a {ARC} {ARC} 0 0 0 {ARC} {} v {} a {ARC} {ARC} 0 0 1 {ARC} {} \
+                         h {} a {ARC} {ARC} 0 0 1 {ARC} {ARC} v {vertical} \
+                         a {ARC} {ARC} 0 0 0 {ARC} {ARC}",
+                        -ARC, -vertical, -ARC, size.width
+                    ),
+                );
+            }
+            Diagram::Repeat { item, separator } => {
+                let size = item.size();
+                let separator_size = separator.as_ref().map(|separator| separator.size());
+                let width = size
+                    .width
+                    .max(separator_size.as_ref().map_or(0, |sep| sep.width));
+                let back = back(&size, separator_size.as_ref());
+                let (left, right) = (x + ARC, x + ARC + width);
+
+                path(svg, format!("M {x} {y} h {ARC}"));
+                item.render(svg, left, y);
+                path(
+                    svg,
+                    format!("M {} {y} h {}", left + size.width, width - size.width + ARC),
+                );
+                // 回环：右侧向下，沿底部向左 (经过分隔符)，再从左侧回到主线
+                path(
+                    svg,
+                    format!(
+                        "M {right} {y} a {ARC} {ARC} 0 0 1 {ARC} {ARC} v {} \
+                         a {ARC} {ARC} 0 0 1 {} {ARC}",
+                        back - 2 * ARC,
+                        -ARC
+                    ),
+                );
+                let bottom = y + back;
+                match (separator, separator_size) {
+                    (Some(separator), Some(sep)) => {
+                        let start = left + (width - sep.width) / 2;
+                        path(
+                            svg,
+                            format!("M {right} {bottom} h {}", start + sep.width - right),
+                        );
+                        separator.render(svg, start, bottom);
+                        path(svg, format!("M {start} {bottom} h {}", left - start));
+                    }
+                    _ => path(svg, format!("M {right} {bottom} h {}", -width)),
+                }
+                path(
+                    svg,
+                    format!(
+                        "M {left} {bottom} a {ARC} {ARC} 0 0 1 {} {} v {} \
+                         a {ARC} {ARC} 0 0 1 {ARC} {}",
+                        -ARC,
+                        -ARC,
+                        -(back - 2 * ARC),
+                        -ARC
+                    ),
+                );
+            }
+        }
+    }
+}
+
+/// 选择中各分支主线相对于第一个分支的偏移
+fn offsets(sizes: &[Size]) -> Vec<i32> {
+    let mut offsets: Vec<i32> = Vec::with_capacity(sizes.len());
+    for (i, size) in sizes.iter().enumerate() {
+        let offset = match i {
+            0 => 0,
+            _ => (offsets[i - 1] + sizes[i - 1].down + V_SPACE + size.up)
+                .max(offsets[i - 1] + 2 * ARC),
+        };
+        offsets.push(offset);
+    }
+    offsets
+}
+
+/// 可选节点上方跳过路线的高度
+fn bypass(size: &Size) -> i32 {
+    (size.up + V_SPACE).max(2 * ARC)
+}
+
+/// 重复节点下方回环的深度
+fn back(size: &Size, separator: Option<&Size>) -> i32 {
+    (size.down + V_SPACE + separator.map_or(0, |sep| sep.up)).max(2 * ARC)
+}
+
+fn text_width(text: &str) -> i32 {
+    text.chars().count() as i32 * CHAR_WIDTH
+}
+
+fn path(svg: &mut String, d: String) {
+    svg.push_str(&format!("<path d=\"{d}\"/>\n"));
+}
+
+fn text_element(svg: &mut String, x: i32, y: i32, text: &str, attrs: &str) {
+    svg.push_str(&format!(
+        "<text x=\"{x}\" y=\"{}\" fill=\"currentColor\" stroke=\"none\"{attrs}>{}</text>\n",
+        y + 4,
+        escape(text)
+    ));
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_choice_layout() {
+        let choice = Diagram::Choice(vec![
+            Diagram::Terminal(String::from("a")),
+            Diagram::Terminal(String::from("bcd")),
+        ]);
+        let size = choice.size();
+        assert_eq!(size.width, 3 * CHAR_WIDTH + 2 * GAP + 4 * ARC);
+        assert_eq!(size.up, BOX);
+        assert_eq!(size.down, BOX + V_SPACE + 2 * BOX);
+
+        let optional = Diagram::Optional(Box::new(choice));
+        assert_eq!(optional.size().up, (BOX + V_SPACE).max(2 * ARC));
+    }
+
+    #[test]
+    fn test_titled() {
+        let titled = Diagram::titled(
+            &Some(Cow::Borrowed("first\n\nsecond")),
+            Diagram::NonTerminal(String::from("Ident")),
+        );
+        assert_eq!(
+            titled.size().width,
+            Diagram::NonTerminal(String::from("Ident")).size().width
+        );
+
+        let mut svg = String::new();
+        titled.render(&mut svg, 0, 0);
+        // 多行文档合并为一行，避免在 rustdoc 中产生空行
+        assert!(svg.starts_with("<g>\n<title>first second</title>\n"));
+        assert!(svg.ends_with("</g>\n"));
+    }
+
+    #[test]
+    fn test_escape() {
+        let mut svg = String::new();
+        text_element(&mut svg, 0, 0, "Vec<Attribute>", "");
+        assert!(svg.contains(">Vec&lt;Attribute&gt;</text>"));
+    }
+}
@@ -1,4 +1,5 @@
 pub mod capture;
+pub mod first;
 pub mod input;
 pub mod keyword;
 pub mod meta;
@@ -1,11 +1,21 @@
-use proc_macro2::Span;
-use quote::quote;
+use std::fmt;
+
+use proc_macro2::{Delimiter, Span, TokenStream};
+use quote::{quote, ToTokens};
 use syn::{
     token::{self},
-    Ident, LitInt, Token, Type,
+    Attribute, Expr, Generics, Ident, LitInt, Token, Type, Visibility,
 };
 
-use crate::ast::{keyword::Keyword, node::Pattern};
+use crate::{
+    ast::{
+        keyword::Keyword,
+        node::{Pattern, PatternKind},
+    },
+    codegen::grammar::describe_alternation,
+    scope_context::used_generics,
+    utils::proc_macro2_crate,
+};
 
 #[derive(Clone)]
 #[cfg_attr(any(feature = "extra-traits", test), derive(Debug))]
@@ -13,10 +23,18 @@ pub struct Capture {
     pub _hash_tag: Token![#],
     pub _paren: token::Paren,
 
+    // 转发到生成字段上的属性 (e.g. `#(/// doc name: Ident)`)
+    pub attrs: Vec<Attribute>,
+
     pub binder: Binder,     // 1. 绑定给谁？
     pub matcher: Matcher,   // 2. 解析什么？
     pub quantity: Quantity, // 3. 解析多少次？
 
+    // 可选字段的缺省值 (e.g. `= expr`)
+    pub default: Option<Expr>,
+    // 解析后对字段的校验 (e.g. `where |v| check(v)`)
+    pub validator: Option<Expr>,
+
     // 用于标记边缘
     pub edge: Option<Keyword>,
 
@@ -43,28 +61,143 @@ pub struct Matcher {
 /// 匹配器
 #[derive(Clone)]
 #[cfg_attr(any(feature = "extra-traits", test), derive(Debug))]
+// syn 类型的大小随 proc-macro2 的 feature (e.g. `span-locations`) 变化
+#[allow(clippy::large_enum_variant)]
 pub enum MatcherKind {
     /// 标准 Syn 类型 (e.g. `Ident`, `Type`)
     SynType(syn::Type),
 
+    /// 使用自定义解析函数的类型 (e.g. `Vec<Attribute> with Attribute::parse_outer`)
+    ///
+    /// `parser` 需要是 `fn(ParseStream) -> syn::Result<T>`
+    ParseWith { ty: Type, parser: Expr },
+
     /// 嵌套结构 (e.g. `#( ... )`)
     Nested(Vec<Pattern>),
 
+    /// 具名的嵌套结构 (e.g. `#[derive(Clone)] pub struct Role as #(ident: Ident)`)
+    ///
+    /// 生成可在外部使用的结构体，并实现 `syn::parse::Parse`
+    Named {
+        item: NamedItem,
+        children: Vec<Pattern>,
+    },
+
     /// 枚举结构 (e.g. `EnumName { Type1, Type2 }`)
     Enum {
         enum_name: Type,
         variants: Vec<(EnumVariant, Matcher)>,
     },
+
+    /// 匿名选择 (e.g. `struct | enum`, `Token![=>] | Token![->]`)
+    ///
+    /// 分支依次命名为 `_0`、`_1`……；所有分支都不产生值时结果为分支索引 `usize`。
+    /// 第二项是解析时按出现位置分配的序号，用于命名匿名选择生成的类型
+    Alternation(Vec<(EnumVariant, Matcher)>, usize),
+
+    /// 记录子模式的位置信息 (e.g. `span(...)`, `tokens(...)`, `Paren(...)`)
+    ///
+    /// 子模式中的捕获直接展开到当前层级
+    Record {
+        kind: RecordKind,
+        children: Vec<Pattern>,
+    },
+
+    /// 无序组 (e.g. `&[,]: name = #(name: LitStr) | #(?: skip)`)
+    ///
+    /// 各成员以首个字面量为键，可按任意顺序出现，每个至多一次；成员中的捕获直接展开到当前层级
+    Unordered {
+        separator: Separator,
+        members: Vec<Member>,
+    },
 }
 
+/// 具名嵌套结构的声明部分 (e.g. `#[derive(Clone)] pub struct Role as`)
 #[derive(Clone)]
 #[cfg_attr(any(feature = "extra-traits", test), derive(Debug))]
-#[allow(dead_code)]
+pub struct NamedItem {
+    /// 未指定 `derive` 时沿用外层的 `derive`
+    pub attrs: Vec<Attribute>,
+    pub visibility: Visibility,
+    pub _struct: Token![struct],
+    pub ident: Ident,
+    pub _as: Token![as],
+}
+
+/// 无序组的成员
+#[derive(Clone)]
+#[cfg_attr(any(feature = "extra-traits", test), derive(Debug))]
+pub struct Member {
+    /// 成员的键，即首个字面量
+    pub key: Keyword,
+    /// 成员的原始模式，决定展开到外层的字段
+    pub pattern: Pattern,
+    /// 匹配到键之后解析的模式（可选成员去掉了 `?`）
+    pub inner: Vec<Pattern>,
+    /// `#(?: ...)` 或 `#(name?: ...)` 形式的成员可以缺省
+    pub optional: bool,
+}
+
+/// 记录的内容
+#[derive(Clone, Copy)]
+#[cfg_attr(any(feature = "extra-traits", test), derive(Debug, PartialEq))]
+pub enum RecordKind {
+    /// `span(...)`：覆盖子模式的 `proc_macro2::Span`
+    Span,
+    /// `tokens(...)`：子模式匹配到的原始 `proc_macro2::TokenStream`
+    Tokens,
+    /// `Paren(...)`、`Brace(...)`、`Bracket(...)`：分组及其分隔符 (e.g. `syn::token::Paren`)
+    Delimiter(Delimiter),
+}
+
+impl RecordKind {
+    pub fn from_ident(ident: &Ident) -> Option<Self> {
+        Some(match ident.to_string().as_str() {
+            "span" => RecordKind::Span,
+            "tokens" => RecordKind::Tokens,
+            "Paren" => RecordKind::Delimiter(Delimiter::Parenthesis),
+            "Brace" => RecordKind::Delimiter(Delimiter::Brace),
+            "Bracket" => RecordKind::Delimiter(Delimiter::Bracket),
+            _ => return None,
+        })
+    }
+
+    /// 记录字段的类型
+    pub fn ty(&self) -> Type {
+        let proc_macro2 = proc_macro2_crate();
+        match self {
+            RecordKind::Span => syn::parse_quote!(#proc_macro2::Span),
+            RecordKind::Tokens => syn::parse_quote!(#proc_macro2::TokenStream),
+            RecordKind::Delimiter(Delimiter::Brace) => syn::parse_quote!(::syn::token::Brace),
+            RecordKind::Delimiter(Delimiter::Bracket) => syn::parse_quote!(::syn::token::Bracket),
+            RecordKind::Delimiter(_) => syn::parse_quote!(::syn::token::Paren),
+        }
+    }
+}
+
+impl MatcherKind {
+    /// 选择的所有分支都不产生值（仅有字面量），此时结果为分支索引
+    pub fn is_index_alternation(&self) -> bool {
+        match self {
+            MatcherKind::Alternation(variants, _) => variants.iter().all(|(v, _)| match v {
+                EnumVariant::Type { .. } => false,
+                EnumVariant::Capture { fields, .. } => fields.is_empty(),
+            }),
+            _ => false,
+        }
+    }
+}
+
+#[derive(Clone)]
+#[cfg_attr(any(feature = "extra-traits", test), derive(Debug))]
+#[allow(dead_code, clippy::large_enum_variant)]
 pub enum EnumVariant {
     Type {
         ident: Type,
         /// 实际是标识符（已判断），解析为Type仅易于开发
         ty: Type,
+        /// 自定义解析函数 (e.g. `Var: Type with path::to::parse_fn`)
+        parser: Option<Box<Expr>>,
     },
     Capture {
         ident: Type,
@@ -78,9 +211,80 @@ pub enum EnumVariant {
 #[derive(Clone)]
 #[cfg_attr(any(feature = "extra-traits", test), derive(Debug, PartialEq))]
 pub enum Quantity {
-    One,                   // 默认
-    Optional,              // ?
-    Many(Option<Keyword>), // * 或 *[,]
+    One,      // 默认
+    Optional, // ?
+    /// * / + / {n} / {n,} / {n,m}，可带分隔符 (e.g. `*[,]`, `+[,]`, `{1,4}[,]`)
+    ///
+    /// `*` 即 `{0,}`，`+` 即 `{1,}`
+    Bounded {
+        separator: Option<Separator>,
+        min: usize,
+        max: Option<usize>,
+    },
+    /// &: 或 !:，前瞻断言，只检查不消费
+    Lookahead {
+        negative: bool,
+    },
+}
+
+/// 重复捕获的分隔符 (e.g. `[,]`, `[,!]`, `[;+]`)
+#[derive(Clone)]
+#[cfg_attr(any(feature = "extra-traits", test), derive(Debug, PartialEq))]
+pub struct Separator {
+    pub keyword: Keyword,
+    pub trailing: Trailing,
+}
+
+/// 末尾分隔符策略
+#[derive(Clone, Copy)]
+#[cfg_attr(any(feature = "extra-traits", test), derive(Debug, PartialEq))]
+pub enum Trailing {
+    /// 默认：末尾分隔符可有可无 (e.g. `[,]`)
+    Optional,
+    /// 禁止末尾分隔符，即纯分隔语义 (e.g. `[,!]`)
+    Forbidden,
+    /// 每个元素后都必须跟随分隔符，即终止符语义 (e.g. `[;+]`)
+    Required,
+}
+
+impl fmt::Display for Separator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.keyword)
+    }
+}
+
+impl ToTokens for Separator {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        self.keyword.to_tokens(tokens);
+    }
+}
+
+impl Quantity {
+    /// 重复捕获的分隔符；非重复捕获返回 `None`
+    pub fn separator(&self) -> Option<&Separator> {
+        match self {
+            Quantity::Bounded { separator, .. } => separator.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// 重复次数限制的描述，用于文档 (e.g. `+`, `{3}`, `{1,4}`)；`*` 为空
+    pub fn bounds_to_string(&self) -> String {
+        match self {
+            Quantity::Bounded {
+                min: 0, max: None, ..
+            } => String::new(),
+            Quantity::Bounded {
+                min: 1, max: None, ..
+            } => String::from("+"),
+            Quantity::Bounded { min, max, .. } => match max {
+                Some(max) if max == min => format!("{{{min}}}"),
+                Some(max) => format!("{{{min},{max}}}"),
+                None => format!("{{{min},}}"),
+            },
+            _ => String::new(),
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -90,12 +294,109 @@ pub struct FieldDef {
     pub ty: Type,
     pub is_optional: bool, // 标记是否已被 Option 包裹
     pub is_inline: bool,
+    pub default: Option<FieldDefault>,
+    /// 捕获上的属性，原样输出到字段上
+    pub attrs: Vec<Attribute>,
+}
+
+/// 可选字段缺省时的处理方式
+///
+/// 解析过程中字段仍以 `Option` 保存，仅在构造最终结构体时转换
+#[derive(Clone)]
+#[cfg_attr(any(feature = "extra-traits", test), derive(Debug, PartialEq))]
+pub enum FieldDefault {
+    /// `#(x?: T = expr)`：缺省时使用 `expr`，结果为 `T`
+    Value { ty: Box<Type>, expr: Box<Expr> },
+    /// `#(flag?: Token![async] = bool)`：仅记录是否出现，结果为 `bool`
+    Flag,
+}
+
+impl FieldDef {
+    /// 缺省值是否生效（字段已被 Option 包裹）
+    pub fn default(&self) -> Option<&FieldDefault> {
+        self.default.as_ref().filter(|_| self.is_optional)
+    }
+
+    /// 最终结构体中字段的类型
+    pub fn public_ty(&self) -> Type {
+        match self.default() {
+            Some(FieldDefault::Value { ty, .. }) => (**ty).clone(),
+            Some(FieldDefault::Flag) => syn::parse_quote!(bool),
+            None => self.ty.clone(),
+        }
+    }
+
+    /// 字段上的 `#[cfg]`，同样作用于变量声明、解析、构造与打印
+    pub fn cfg_attrs(&self) -> Vec<&Attribute> {
+        cfg_attrs(&self.attrs)
+    }
+}
+
+pub fn cfg_attrs(attrs: &[Attribute]) -> Vec<&Attribute> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("cfg"))
+        .collect()
+}
+
+/// `Token![...]` 类型不含值，可以通过 `Default` 重新构造
+pub fn is_token_type(ty: &Type) -> bool {
+    match ty {
+        Type::Macro(ty) => ty
+            .mac
+            .path
+            .segments
+            .last()
+            .map(|s| s.ident == "Token")
+            .unwrap_or(false),
+        _ => false,
+    }
+}
+
+/// 缺省值 `= bool` 表示只记录是否出现 (e.g. `#(flag?: Token![async] = bool)`)
+pub fn is_flag_marker(expr: &Expr) -> bool {
+    matches!(expr, Expr::Path(path) if path.qself.is_none() && path.path.is_ident("bool"))
+}
+
+impl Matcher {
+    /// `Token![...]` 与只含字面量的嵌套模式可以重新构造，因此可以只记录是否出现
+    pub fn is_flaggable(&self) -> bool {
+        match &self.kind {
+            MatcherKind::SynType(ty) => is_token_type(ty),
+            MatcherKind::Nested(children) => {
+                !children.is_empty()
+                    && children
+                        .iter()
+                        .all(|p| matches!(p.kind, PatternKind::Literal(_)))
+            }
+            _ => false,
+        }
+    }
 }
 
 impl Capture {
     pub fn collect_captures(&self) -> Vec<FieldDef> {
+        // 前瞻断言不产生任何字段
+        if let Quantity::Lookahead { .. } = self.quantity {
+            return vec![];
+        }
         // 1. 先收集原始字段 (Base Fields)
         let mut fields = self.matcher.collect_captures(&self.binder);
+        if let [field] = fields.as_mut_slice() {
+            if self.is_flag() {
+                field.default = Some(FieldDefault::Flag);
+            } else if let Some(expr) = &self.default {
+                field.default = Some(FieldDefault::Value {
+                    ty: Box::new(field.ty.clone()),
+                    expr: Box::new(expr.clone()),
+                });
+            }
+        }
+        if let Binder::Named(name) = &self.binder {
+            if let Some(field) = fields.iter_mut().find(|f| &f.name == name) {
+                field.attrs = self.attrs.clone();
+            }
+        }
         // 2. 根据当前的 Quantity 对字段类型进行“包装” (Type Wrapping)
         // 这就是解决 #(?: #(ret: Type)) 问题的关键
         self.apply_quantity_wrapping(&mut fields);
@@ -103,13 +404,47 @@ impl Capture {
         fields
     }
 
+    /// 捕获上的文档注释，每行去掉首尾空白后以换行连接
+    pub fn doc(&self) -> Option<String> {
+        let lines: Vec<String> = self
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("doc"))
+            .filter_map(|attr| match &attr.meta {
+                syn::Meta::NameValue(syn::MetaNameValue {
+                    value:
+                        Expr::Lit(syn::ExprLit {
+                            lit: syn::Lit::Str(lit),
+                            ..
+                        }),
+                    ..
+                }) => Some(lit.value()),
+                _ => None,
+            })
+            .flat_map(|doc| {
+                doc.lines()
+                    .map(|line| line.trim().to_string())
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        let doc = lines.join("\n").trim().to_string();
+        (!doc.is_empty()).then_some(doc)
+    }
+
+    /// `#(flag?: Token![...] = bool)` 与只含字面量的 `#(flag?: k = bool)` 仅记录是否出现
+    pub fn is_flag(&self) -> bool {
+        matches!(self.quantity, Quantity::Optional)
+            && self.default.as_ref().is_some_and(is_flag_marker)
+            && self.matcher.is_flaggable()
+    }
+
     fn apply_quantity_wrapping(&self, fields: &mut Vec<FieldDef>) {
         if fields.is_empty() {
             return;
         }
 
         match &self.quantity {
-            Quantity::One => {
+            Quantity::One | Quantity::Lookahead { .. } => {
                 // 默认情况，不做改变
             }
             Quantity::Optional => {
@@ -123,8 +458,9 @@ impl Capture {
                     }
                 }
             }
-            Quantity::Many(sep) => {
-                // 对应 * 或 *[,]
+            Quantity::Bounded { .. } => {
+                // 对应 * 或 *[,]（以及有界重复）
+                let sep = self.quantity.separator();
                 for field in fields {
                     let ty = &field.ty;
                     // Punctuated 本身就是容器，通常不需要再标 is_optional
@@ -142,44 +478,87 @@ impl Capture {
 }
 
 impl Matcher {
-    fn collect_captures(&self, binder: &Binder) -> Vec<FieldDef> {
+    pub fn collect_captures(&self, binder: &Binder) -> Vec<FieldDef> {
         match &self.kind {
-            MatcherKind::SynType(ty) | MatcherKind::Enum { enum_name: ty, .. } => {
+            MatcherKind::SynType(ty)
+            | MatcherKind::ParseWith { ty, .. }
+            | MatcherKind::Enum { enum_name: ty, .. } => {
                 generate_captures(ty, binder)
                     .map(|def| vec![def])
                     .unwrap_or_default()
                 // 处理叶子节点：只有 Named 和 Inline 产生字段
             }
 
+            MatcherKind::Alternation(variants, index) => {
+                let name = match binder {
+                    Binder::Named(ident) => ident.clone(),
+                    Binder::Inline(idx) => quote::format_ident!("_{}", idx),
+                    // 匿名选择仅作验证
+                    Binder::Anonymous => return vec![],
+                };
+                let ty = if self.kind.is_index_alternation() {
+                    syn::parse_quote!(usize)
+                } else {
+                    let type_name = alternation_item_ident(binder, *index);
+                    let generics = variants_generics(variants);
+                    let (_, ty_generics, _) = generics.split_for_impl();
+                    if let Some(scope) = crate::scope_context::get_scope_ident() {
+                        syn::parse_quote!(#scope::#type_name #ty_generics)
+                    } else {
+                        syn::parse_quote!(#type_name #ty_generics)
+                    }
+                };
+                vec![FieldDef {
+                    name,
+                    ty,
+                    is_optional: false,
+                    is_inline: matches!(binder, Binder::Inline(_)),
+                    default: None,
+                    attrs: Vec::new(),
+                }]
+            }
+
+            MatcherKind::Named { item, children } => {
+                generate_captures(&named_item_ty(&item.ident, children), binder)
+                    .map(|def| vec![def])
+                    .unwrap_or_default()
+            }
+
+            MatcherKind::Record { kind, children } => generate_captures(&kind.ty(), binder)
+                .into_iter()
+                .chain(children.iter().flat_map(|p| p.collect_captures()))
+                .collect(),
+
+            MatcherKind::Unordered { members, .. } => members
+                .iter()
+                .flat_map(|m| m.pattern.collect_captures())
+                .collect(),
+
             MatcherKind::Nested(children) => {
                 match binder {
                     Binder::Named(ident) => {
                         let type_name = quote::format_ident!("{}_Item", ident);
-                        let ty = if let Some(scope) = crate::scope_context::get_scope_ident() {
-                            syn::parse_quote!(#scope::#type_name)
-                        } else {
-                            syn::parse_quote!(#type_name)
-                        };
+                        let ty = nested_item_ty(&type_name, children);
                         vec![FieldDef {
                             name: ident.clone(),
                             ty,
                             is_optional: false,
                             is_inline: false,
+                            default: None,
+                            attrs: Vec::new(),
                         }]
                     }
                     Binder::Inline(idx) => {
                         let ident = LitInt::new(&idx.to_string(), Span::call_site());
                         let type_name = quote::format_ident!("_{ident}");
-                        let ty = if let Some(scope) = crate::scope_context::get_scope_ident() {
-                            syn::parse_quote!(#scope::#type_name)
-                        } else {
-                            syn::parse_quote!(#type_name)
-                        };
+                        let ty = nested_item_ty(&type_name, children);
                         vec![FieldDef {
                             name: type_name,
                             ty,
                             is_optional: false,
                             is_inline: true,
+                            default: None,
+                            attrs: Vec::new(),
                         }]
                     }
                     Binder::Anonymous => {
@@ -192,6 +571,49 @@ impl Matcher {
     }
 }
 
+/// 选择生成的类型名：具名选择为 `name_Item`，其余以出现位置命名 (e.g. `_Alt_0`)，
+/// 以免与行内捕获的变量 `_0` 同名
+pub fn alternation_item_ident(binder: &Binder, index: usize) -> Ident {
+    match binder {
+        Binder::Named(ident) => quote::format_ident!("{}_Item", ident),
+        _ => quote::format_ident!("_Alt_{}", index),
+    }
+}
+
+/// 嵌套结构体的类型路径，带上其字段用到的泛型参数 (e.g. `scope::name_Item<T>`)
+fn nested_item_ty(type_name: &Ident, children: &[Pattern]) -> Type {
+    let ty = named_item_ty(type_name, children);
+    if let Some(scope) = crate::scope_context::get_scope_ident() {
+        syn::parse_quote!(#scope::#ty)
+    } else {
+        ty
+    }
+}
+
+/// 具名嵌套结构体的类型，定义在私有作用域之外 (e.g. `Role<T>`)
+pub fn named_item_ty(type_name: &Ident, children: &[Pattern]) -> Type {
+    let tys = children
+        .iter()
+        .flat_map(|p| p.collect_captures())
+        .map(|f| f.public_ty());
+    let generics = used_generics(quote! { #(#tys)* });
+    let (_, ty_generics, _) = generics.split_for_impl();
+    syn::parse_quote!(#type_name #ty_generics)
+}
+
+/// 选择对应的枚举用到的泛型参数
+pub fn variants_generics(variants: &[(EnumVariant, Matcher)]) -> Generics {
+    let tys = variants.iter().flat_map(|(v, _)| match v {
+        EnumVariant::Type { ty, .. } => vec![ty.clone()],
+        EnumVariant::Capture { pattern, .. } => pattern
+            .collect_captures()
+            .iter()
+            .map(FieldDef::public_ty)
+            .collect(),
+    });
+    used_generics(quote! { #(#tys)* })
+}
+
 fn generate_captures(ty: &Type, binder: &Binder) -> Option<FieldDef> {
     match binder {
         Binder::Named(ident) => Some(FieldDef {
@@ -199,12 +621,16 @@ fn generate_captures(ty: &Type, binder: &Binder) -> Option<FieldDef> {
             ty: ty.clone(),
             is_optional: false, // 初始状态
             is_inline: false,
+            default: None,
+            attrs: Vec::new(),
         }),
         Binder::Inline(idx) => Some(FieldDef {
             name: quote::format_ident!("_{}", idx),
             ty: ty.clone(),
             is_optional: false,
             is_inline: true,
+            default: None,
+            attrs: Vec::new(),
         }),
         Binder::Anonymous => None, // _: Type 不产生字段
     }
@@ -223,6 +649,8 @@ pub enum ExampleItem {
     },
     Block {
         optional: bool,
+        /// 重复捕获的次数限制描述（`*` 为空字符串），非重复捕获为 `None`
+        repeat: Option<String>,
         example: Vec<ExampleItem>,
         iter: String,
     },
@@ -247,7 +675,7 @@ trait CollectExample {
 impl CollectExample for MatcherEnumVariant {
     fn collect_example(&self) -> Vec<ExampleItem> {
         match self {
-            (EnumVariant::Type { ident, ty }, ..) => {
+            (EnumVariant::Type { ident, ty, .. }, ..) => {
                 vec![ExampleItem::Capture {
                     name: quote! {#ident}.to_string(),
                     ty: quote! {#ty}.to_string(),
@@ -266,20 +694,24 @@ impl Matcher {
         };
         let wrapper = |items: Vec<ExampleItem>| match quantity {
             Quantity::One => items,
-            Quantity::Many(sep) => {
-                let iter = if let Some(sep) = sep {
+            // 前瞻断言不消费输入，不出现在示例中
+            Quantity::Lookahead { .. } => vec![],
+            Quantity::Bounded { .. } => {
+                let iter = if let Some(sep) = quantity.separator() {
                     sep.to_string()
                 } else {
                     String::new()
                 };
                 vec![ExampleItem::Block {
                     optional: false,
+                    repeat: Some(quantity.bounds_to_string()),
                     example: items,
                     iter,
                 }]
             }
             Quantity::Optional => vec![ExampleItem::Block {
                 optional: true,
+                repeat: None,
                 example: items,
                 iter: String::new(),
             }],
@@ -301,6 +733,7 @@ impl Matcher {
                             } else {
                                 ExampleItem::Block {
                                     optional: false,
+                                    repeat: None,
                                     example: examples,
                                     iter: String::new(),
                                 }
@@ -309,13 +742,49 @@ impl Matcher {
                         .collect(),
                 }]
             }
-            MatcherKind::SynType(ty) => {
+            MatcherKind::SynType(ty) | MatcherKind::ParseWith { ty, .. } => {
                 vec![ExampleItem::Capture {
                     name: name.to_string(),
                     ty: quote! {#ty}.to_string(),
                 }]
             }
-            MatcherKind::Nested(nest) => nest.iter().flat_map(|n| n.collect_example()).collect(),
+            MatcherKind::Alternation(variants, _) => {
+                vec![ExampleItem::Capture {
+                    name: name.to_string(),
+                    ty: describe_alternation(variants),
+                }]
+            }
+            MatcherKind::Nested(nest) | MatcherKind::Named { children: nest, .. } => {
+                nest.iter().flat_map(|n| n.collect_example()).collect()
+            }
+            MatcherKind::Record { kind, children } => {
+                let example = children.iter().flat_map(|n| n.collect_example()).collect();
+                let delimiter = match kind {
+                    RecordKind::Delimiter(Delimiter::Brace) => ("{", "}"),
+                    RecordKind::Delimiter(Delimiter::Bracket) => ("[", "]"),
+                    RecordKind::Delimiter(_) => ("(", ")"),
+                    RecordKind::Span | RecordKind::Tokens => return wrapper(example),
+                };
+                vec![ExampleItem::Group {
+                    delimiter: (delimiter.0.to_string(), delimiter.1.to_string()),
+                    example,
+                }]
+            }
+            MatcherKind::Unordered { separator, members } => {
+                let mut example = vec![];
+                for (i, member) in members.iter().enumerate() {
+                    if i > 0 {
+                        example.push(ExampleItem::Literal(separator.to_string()));
+                    }
+                    example.extend(member.pattern.collect_example());
+                }
+                vec![ExampleItem::Block {
+                    optional: false,
+                    repeat: None,
+                    example,
+                    iter: String::new(),
+                }]
+            }
         };
         wrapper(items)
     }
@@ -324,8 +793,8 @@ impl Matcher {
 #[cfg(test)]
 mod tests {
     use crate::{
-        ast::{keyword::Keyword, node::PatternKind},
-        codegen::logic::Compiler,
+        ast::{keyword::Keyword, meta::SemanticInfo, node::PatternKind},
+        codegen::{grammar::describe_pattern, logic::Compiler},
         scope_context::reset_inline_counter,
         syntax::context::ParseContext,
     };
@@ -367,6 +836,15 @@ mod tests {
         parser.parse2(input)
     }
 
+    // 与报错中的描述一致
+    fn describe(capture: Capture) -> String {
+        describe_pattern(&Pattern {
+            kind: PatternKind::Capture(Box::new(capture)),
+            span: Span::call_site(),
+            meta: None,
+        })
+    }
+
     fn parse_capture_matcher(input: TokenStream, ctx: &mut ParseContext) -> Result<Matcher> {
         let parser = move |input: ParseStream| Matcher::parse(input, ctx);
         parser.parse2(input)
@@ -403,8 +881,17 @@ mod tests {
         let input = quote! { #(list*[,]: Ident) };
         let capture: Capture = parse_capture(input, ctx).unwrap();
         assert_named(&capture, "list");
-        if let Quantity::Many(sep) = &capture.quantity {
-            if let Some(Keyword::Rust(s)) = sep {
+        if let Quantity::Bounded {
+            separator: sep,
+            min: 0,
+            max: None,
+        } = &capture.quantity
+        {
+            if let Some(Separator {
+                keyword: Keyword::Rust(s),
+                trailing: Trailing::Optional,
+            }) = sep
+            {
                 assert_eq!(s, ",");
             } else {
                 panic!("Expected Rust keyword separator");
@@ -414,6 +901,575 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_iter_trailing() {
+        let ctx = &mut ParseContext::default();
+
+        let trailing = |input: TokenStream, ctx: &mut ParseContext| {
+            let capture = parse_capture(input, ctx).unwrap();
+            let sep = capture.quantity.separator().expect("Expected separator");
+            (sep.to_string(), sep.trailing)
+        };
+
+        assert_eq!(
+            trailing(quote! { #(list*[,]: Ident) }, ctx),
+            (",".to_string(), Trailing::Optional)
+        );
+        assert_eq!(
+            trailing(quote! { #(list*[,!]: Ident) }, ctx),
+            (",".to_string(), Trailing::Forbidden)
+        );
+        assert_eq!(
+            trailing(quote! { #(list*[;+]: Ident) }, ctx),
+            (";".to_string(), Trailing::Required)
+        );
+        assert_eq!(
+            trailing(quote! { #(list{1,4}[,!]: Ident) }, ctx),
+            (",".to_string(), Trailing::Forbidden)
+        );
+
+        let err = parse_capture(quote! { #(list*[, ?]: Ident) }, ctx).unwrap_err();
+        assert_eq!(err.to_string(), "expected '!' or '+' after the separator");
+        let err = parse_capture(quote! { #(list*[, ! !]: Ident) }, ctx).unwrap_err();
+        assert_eq!(err.to_string(), "expected ']' after the separator");
+    }
+
+    #[test]
+    fn test_parse_iter_without_separator() {
+        let ctx = &mut ParseContext::default();
+
+        // 语法: name*: Type
+        let input = quote! { #(list*: Ident) };
+        let capture: Capture = parse_capture(input, ctx).unwrap();
+        assert_named(&capture, "list");
+        assert_eq!(
+            capture.quantity,
+            Quantity::Bounded {
+                separator: None,
+                min: 0,
+                max: None
+            }
+        );
+
+        let fields = capture.collect_captures();
+        assert_eq!(fields[0].ty, parse_quote!(::std::vec::Vec<Ident>));
+    }
+
+    #[test]
+    fn test_parse_bounded() {
+        let ctx = &mut ParseContext::default();
+
+        let bounded = |input: TokenStream, ctx: &mut ParseContext| {
+            let capture = parse_capture(input, ctx).unwrap();
+            let Quantity::Bounded {
+                separator,
+                min,
+                max,
+            } = capture.quantity
+            else {
+                panic!("Expected Bounded quantity, got {:?}", capture.quantity);
+            };
+            (separator.map(|s| s.to_string()), min, max)
+        };
+
+        assert_eq!(
+            bounded(quote! { #(x+[,]: Ident) }, ctx),
+            (Some(",".to_string()), 1, None)
+        );
+        assert_eq!(bounded(quote! { #(x+: Ident) }, ctx), (None, 1, None));
+        assert_eq!(
+            bounded(quote! { #(x{3}[,]: Ident) }, ctx),
+            (Some(",".to_string()), 3, Some(3))
+        );
+        assert_eq!(
+            bounded(quote! { #(x{1,4}[;]: Ident) }, ctx),
+            (Some(";".to_string()), 1, Some(4))
+        );
+        assert_eq!(bounded(quote! { #(x{2,}: Ident) }, ctx), (None, 2, None));
+        assert_eq!(bounded(quote! { #({2,}[,]: Ident) }, ctx).1, 2);
+
+        let capture = parse_capture(quote! { #(x{1,4}[,]: Ident) }, ctx).unwrap();
+        let fields = capture.collect_captures();
+        assert_eq!(
+            fields[0].ty,
+            parse_quote!(::syn::punctuated::Punctuated<Ident, ::syn::Token![,]>)
+        );
+    }
+
+    #[test]
+    fn test_parse_bounded_error() {
+        let ctx = &mut ParseContext::default();
+
+        let err = parse_capture(quote! { #(x{4,1}[,]: Ident) }, ctx).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid repetition bounds: 4 is greater than 1"
+        );
+
+        let err = parse_capture(quote! { #(x{0}[,]: Ident) }, ctx).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "the maximum number of repetitions must be greater than 0"
+        );
+
+        let err = parse_capture(quote! { #(x{1,2,3}[,]: Ident) }, ctx).unwrap_err();
+        assert_eq!(err.to_string(), "expected '{n}', '{n,}' or '{n,m}'");
+    }
+
+    #[test]
+    fn test_parse_alternation() {
+        let ctx = &mut ParseContext::default();
+
+        let capture = parse_capture(quote! { #(kind: struct | enum) }, ctx).unwrap();
+        let MatcherKind::Alternation(variants, _) = &capture.matcher.kind else {
+            panic!("Expected Alternation, got {:?}", capture.matcher.kind);
+        };
+        assert_eq!(variants.len(), 2);
+        assert!(capture.matcher.kind.is_index_alternation());
+        assert_eq!(describe_alternation(variants), "struct | enum");
+        let fields = capture.collect_captures();
+        assert_eq!(fields[0].ty, parse_quote!(usize));
+
+        let capture = parse_capture(quote! { #(arrow: Token![=>] | Token![->]) }, ctx).unwrap();
+        assert!(!capture.matcher.kind.is_index_alternation());
+        let fields = capture.collect_captures();
+        assert_eq!(fields[0].ty, parse_quote!(arrow_Item));
+
+        let capture = parse_capture(quote! { #(Ident | LitInt) }, ctx).unwrap();
+        assert_eq!(capture.binder, Binder::Anonymous);
+        assert!(capture.collect_captures().is_empty());
+
+        let capture = parse_capture(quote! { #(pub | priv) }, ctx).unwrap();
+        assert!(matches!(capture.matcher.kind, MatcherKind::Alternation(..)));
+
+        // 复合符号与空分支中的 `|` 仍作为字面量
+        let capture = parse_capture(quote! { #(?: -> || =>) }, ctx).unwrap();
+        assert!(matches!(capture.matcher.kind, MatcherKind::Nested(_)));
+        let capture = parse_capture(quote! { #(?: | #(x: Ident) |) }, ctx).unwrap();
+        assert!(matches!(capture.matcher.kind, MatcherKind::Nested(_)));
+
+        // 只有单次捕获才切分选择，其余形式中的 `|` 是字面量
+        let capture = parse_capture(quote! { #(?: #(a: Ident) | #(b: Ident)) }, ctx).unwrap();
+        assert!(matches!(capture.matcher.kind, MatcherKind::Nested(_)));
+        let fields = capture.collect_captures();
+        assert_eq!(fields.len(), 2);
+        assert!(fields.iter().all(|f| f.is_optional));
+        let capture = parse_capture(quote! { #(*: #(Ident) | #(LitInt)) }, ctx).unwrap();
+        assert!(matches!(capture.matcher.kind, MatcherKind::Nested(_)));
+    }
+
+    #[test]
+    fn test_parse_alternation_error() {
+        let ctx = &mut ParseContext::default();
+
+        let err = parse_capture(quote! { #(fn #(name: Ident) | struct) }, ctx).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "captures inside an anonymous alternation are not supported, bind the alternation with a name like '#(kind: ...)'"
+        );
+    }
+
+    #[test]
+    fn test_parse_named_nested() {
+        let ctx = &mut ParseContext::default();
+
+        let capture = parse_capture(
+            quote! { #(roles*[,]: #[derive(Clone)] pub struct Role as #(ident: Ident)) },
+            ctx,
+        )
+        .unwrap();
+        let MatcherKind::Named { item, children } = &capture.matcher.kind else {
+            panic!("Expected Named, got {:?}", capture.matcher.kind);
+        };
+        assert_eq!(item.ident, "Role");
+        assert_eq!(item.attrs.len(), 1);
+        assert!(matches!(item.visibility, Visibility::Public(_)));
+        assert_eq!(children.len(), 1);
+        // 字段类型为具名结构体本身，而非私有作用域中的 `roles_Item`
+        let fields = capture.collect_captures();
+        assert_eq!(
+            fields[0].ty,
+            parse_quote!(::syn::punctuated::Punctuated<Role, ::syn::Token![,]>)
+        );
+
+        // 以小写字面量开头的普通模式不受影响
+        let capture = parse_capture(quote! { #(alias?: as #(name: Ident)) }, ctx).unwrap();
+        assert!(matches!(capture.matcher.kind, MatcherKind::Nested(_)));
+        // 没有 `struct` 时 `ident as ...` 是字面量模式 (e.g. `value as u8`)
+        let capture = parse_capture(quote! { #(cast?: value as #(ty: Type)) }, ctx).unwrap();
+        let MatcherKind::Nested(children) = &capture.matcher.kind else {
+            panic!("Expected Nested, got {:?}", capture.matcher.kind);
+        };
+        let literals: Vec<_> = children
+            .iter()
+            .filter_map(|child| match &child.kind {
+                PatternKind::Literal(keyword) => Some(keyword.to_string()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(literals, ["value", "as"]);
+
+        let err = parse_capture(quote! { #(x: struct Pair as #(@: Ident)) }, ctx).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "named nested captures need named fields, found an inline capture `#(@: ...)`"
+        );
+        let err = parse_capture(quote! { #(x: struct Pair as Ident) }, ctx).unwrap_err();
+        assert_eq!(err.to_string(), "expected a pattern after `Pair as`");
+    }
+
+    #[test]
+    fn test_parse_attrs() {
+        let ctx = &mut ParseContext::default();
+
+        let capture = parse_capture(
+            quote! { #(/// 函数名
+            ///   可选
+            #[allow(dead_code)] name?: Ident) },
+            ctx,
+        )
+        .unwrap();
+        assert_eq!(capture.attrs.len(), 3);
+        assert_eq!(capture.doc().as_deref(), Some("函数名\n可选"));
+        // 属性随字段一同输出，`Option` 包装不影响
+        let fields = capture.collect_captures();
+        assert_eq!(fields[0].attrs, capture.attrs);
+
+        let capture = parse_capture(quote! { #(name: Ident) }, ctx).unwrap();
+        assert_eq!(capture.doc(), None);
+
+        let err = parse_capture(quote! { #(#[doc = "x"] @: Ident) }, ctx).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "attributes are only supported on named captures like '#(#[doc = \"...\"] name: Ident)'"
+        );
+        let err = parse_capture(quote! { #(#[doc = "x"] Ident) }, ctx).unwrap_err();
+        assert!(err.to_string().starts_with("attributes are only supported"));
+
+        // `#[cfg]` 保留在字段上，同时作用于解析、构造与打印
+        let capture = parse_capture(quote! { #(#[cfg(test)] name: Ident) }, ctx).unwrap();
+        let fields = capture.collect_captures();
+        assert_eq!(fields[0].cfg_attrs().len(), 1);
+
+        let err = parse_capture(quote! { #(#[cfg(test)] name: span(Ident)) }, ctx).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "'#[cfg]' is not supported on span(...), tokens(...) and delimiter records"
+        );
+        let err = parse_capture(quote! { #(?: #(#[cfg(test)] a: Ident)) }, ctx).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "'#[cfg]' is not supported on captures inside an anonymous optional or repeated group"
+        );
+    }
+
+    #[test]
+    fn test_parse_lookahead() {
+        let ctx = &mut ParseContext::default();
+
+        let capture = parse_capture(quote! { #(!: Token![where]) }, ctx).unwrap();
+        assert_eq!(capture.binder, Binder::Anonymous);
+        assert_eq!(capture.quantity, Quantity::Lookahead { negative: true });
+        assert!(matches!(capture.matcher.kind, MatcherKind::SynType(_)));
+
+        let capture = parse_capture(quote! { #(&: #(Ident) =) }, ctx).unwrap();
+        assert_eq!(capture.quantity, Quantity::Lookahead { negative: false });
+        assert!(matches!(capture.matcher.kind, MatcherKind::Nested(_)));
+        assert!(capture.collect_captures().is_empty());
+        assert!(capture.collect_example().is_empty());
+
+        let capture = parse_capture(quote! { #(!: #(struct | enum)) }, ctx).unwrap();
+        let MatcherKind::Nested(children) = &capture.matcher.kind else {
+            panic!("Expected Nested, got {:?}", capture.matcher.kind);
+        };
+        assert!(matches!(
+            &children[0].kind,
+            PatternKind::Capture(c) if matches!(c.matcher.kind, MatcherKind::Alternation(..))
+        ));
+    }
+
+    #[test]
+    fn test_parse_lookahead_error() {
+        let ctx = &mut ParseContext::default();
+
+        let err = parse_capture(quote! { #(x!: Ident) }, ctx).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "lookahead assertions cannot be bound, use '#(&: ...)' or '#(!: ...)'"
+        );
+
+        let err = parse_capture(quote! { #(&: #(x: Ident) =) }, ctx).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "captures inside a lookahead assertion are not supported"
+        );
+    }
+
+    #[test]
+    fn test_parse_record() {
+        let ctx = &mut ParseContext::default();
+
+        let capture = parse_capture(quote! { #(ret_span?: span(-> #(ret: Type))) }, ctx).unwrap();
+        let MatcherKind::Record { kind, children } = &capture.matcher.kind else {
+            panic!("Expected Record, got {:?}", capture.matcher.kind);
+        };
+        assert_eq!(*kind, RecordKind::Span);
+        assert_eq!(children.len(), 2);
+        let fields = capture.collect_captures();
+        assert_eq!(fields.len(), 2);
+        assert_eq!(fields[0].name.to_string(), "ret_span");
+        let proc_macro2 = proc_macro2_crate();
+        assert_eq!(
+            fields[0].ty,
+            parse_quote!(::std::option::Option<#proc_macro2::Span>)
+        );
+        assert_eq!(fields[1].name.to_string(), "ret");
+        assert!(fields.iter().all(|f| f.is_optional));
+
+        let capture = parse_capture(quote! { #(raw: tokens(#(path: Path))) }, ctx).unwrap();
+        let fields = capture.collect_captures();
+        assert_eq!(fields[0].ty, parse_quote!(#proc_macro2::TokenStream));
+        assert_eq!(describe(capture), "<Path>");
+
+        let capture = parse_capture(quote! { #(paren: Paren(#(args*[,]: Expr))) }, ctx).unwrap();
+        let fields = capture.collect_captures();
+        assert_eq!(fields[0].ty, parse_quote!(::syn::token::Paren));
+        assert!(matches!(
+            capture.collect_example().as_slice(),
+            [ExampleItem::Group { .. }]
+        ));
+    }
+
+    #[test]
+    fn test_parse_record_error() {
+        let ctx = &mut ParseContext::default();
+
+        let err = parse_capture(quote! { #(?: span(-> #(ret: Type))) }, ctx).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "span(...), tokens(...) and delimiter records need a name like '#(name: span(...))'"
+        );
+
+        let err = parse_capture(quote! { #(x*[,]: Brace(#(y: Ident))) }, ctx).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "span(...), tokens(...) and delimiter records only support the '?' quantifier"
+        );
+
+        let err = parse_capture(quote! { #(x: span(a) b) }, ctx).unwrap_err();
+        assert_eq!(err.to_string(), "Unexpected 'b'");
+    }
+
+    #[test]
+    fn test_parse_with() {
+        let ctx = &mut ParseContext::default();
+
+        let capture = parse_capture(
+            quote! { #(attrs: Vec<Attribute> with Attribute::parse_outer) },
+            ctx,
+        )
+        .unwrap();
+        let MatcherKind::ParseWith { ty, parser } = &capture.matcher.kind else {
+            panic!("Expected ParseWith, got {:?}", capture.matcher.kind);
+        };
+        assert_eq!(*ty, parse_quote!(Vec<Attribute>));
+        assert_eq!(*parser, parse_quote!(Attribute::parse_outer));
+        let fields = capture.collect_captures();
+        assert_eq!(fields[0].ty, parse_quote!(Vec<Attribute>));
+
+        let capture = parse_capture(quote! { #(Ident with Ident::parse_any) }, ctx).unwrap();
+        assert_eq!(capture.binder, Binder::Anonymous);
+        assert!(matches!(
+            capture.matcher.kind,
+            MatcherKind::ParseWith { .. }
+        ));
+
+        let capture = parse_capture(
+            quote! { #(token: Token { Word: Ident with Ident::parse_any, LitInt }) },
+            ctx,
+        )
+        .unwrap();
+        let MatcherKind::Enum { variants, .. } = &capture.matcher.kind else {
+            panic!("Expected Enum, got {:?}", capture.matcher.kind);
+        };
+        assert!(matches!(
+            &variants[0],
+            (
+                EnumVariant::Type {
+                    parser: Some(_),
+                    ..
+                },
+                Matcher {
+                    kind: MatcherKind::ParseWith { .. },
+                    ..
+                }
+            )
+        ));
+        assert!(matches!(
+            &variants[1],
+            (
+                EnumVariant::Type { parser: None, .. },
+                Matcher {
+                    kind: MatcherKind::SynType(_),
+                    ..
+                }
+            )
+        ));
+    }
+
+    #[test]
+    fn test_parse_with_error() {
+        let ctx = &mut ParseContext::default();
+
+        let err = parse_capture(quote! { #(name: Ident with) }, ctx).unwrap_err();
+        assert_eq!(err.to_string(), "expected a parse function after 'with'");
+
+        let err = parse_capture(quote! { #(name: Ident with a b) }, ctx).unwrap_err();
+        assert_eq!(err.to_string(), "Unexpected 'b'");
+    }
+
+    #[test]
+    fn test_parse_validator() {
+        let ctx = &mut ParseContext::default();
+
+        let capture =
+            parse_capture(quote! { #(size: LitInt where |v| check_size(v)) }, ctx).unwrap();
+        assert!(matches!(capture.matcher.kind, MatcherKind::SynType(_)));
+        assert_eq!(capture.validator, Some(parse_quote!(|v| check_size(v))));
+
+        let capture =
+            parse_capture(quote! { #(keys*[,]: Ident where |v| unique(v)) }, ctx).unwrap();
+        assert!(matches!(
+            capture.quantity,
+            Quantity::Bounded {
+                separator: Some(_),
+                ..
+            }
+        ));
+        assert!(matches!(capture.validator, Some(Expr::Closure(_))));
+
+        // `where` 之后不是闭包时，仍作为字面量
+        let capture = parse_capture(
+            quote! { #(bounds?: where #(preds*[,]: WherePredicate)) },
+            ctx,
+        )
+        .unwrap();
+        assert!(capture.validator.is_none());
+        assert!(matches!(capture.matcher.kind, MatcherKind::Nested(_)));
+        let capture = parse_capture(quote! { #(clause?: #(ty: Type) where Self) }, ctx).unwrap();
+        assert!(capture.validator.is_none());
+        assert!(matches!(capture.matcher.kind, MatcherKind::Nested(_)));
+    }
+
+    #[test]
+    fn test_parse_default() {
+        let ctx = &mut ParseContext::default();
+
+        let capture = parse_capture(quote! { #(ty?: Type = parse_quote!(())) }, ctx).unwrap();
+        assert_eq!(capture.quantity, Quantity::Optional);
+        assert!(matches!(capture.matcher.kind, MatcherKind::SynType(_)));
+        assert!(matches!(capture.default, Some(Expr::Macro(_))));
+        let fields = capture.collect_captures();
+        assert_eq!(fields[0].ty, parse_quote!(::std::option::Option<Type>));
+        assert_eq!(fields[0].public_ty(), parse_quote!(Type));
+
+        // 缺省值与校验子句可以同时存在
+        let capture = parse_capture(
+            quote! { #(n?: LitInt = default_n() where |n| check_n(n)) },
+            ctx,
+        )
+        .unwrap();
+        assert_eq!(capture.default, Some(parse_quote!(default_n())));
+        assert_eq!(capture.validator, Some(parse_quote!(|n| check_n(n))));
+
+        // 复合符号中的 `=` 不作为缺省值
+        let capture = parse_capture(quote! { #(arrow?: Token![=>]) }, ctx).unwrap();
+        assert!(capture.default.is_none());
+
+        // `Token![...]` 的可选捕获默认为 `Option`，`= bool` 时只记录是否出现
+        let fields = parse_capture(quote! { #(asyncness?: Token![async]) }, ctx)
+            .unwrap()
+            .collect_captures();
+        assert!(fields[0].default.is_none());
+        assert_eq!(
+            fields[0].public_ty(),
+            parse_quote!(::std::option::Option<Token![async]>)
+        );
+        let fields = parse_capture(quote! { #(asyncness?: Token![async] = bool) }, ctx)
+            .unwrap()
+            .collect_captures();
+        assert_eq!(fields[0].default, Some(FieldDefault::Flag));
+        assert_eq!(fields[0].public_ty(), parse_quote!(bool));
+    }
+
+    #[test]
+    fn test_parse_default_error() {
+        let ctx = &mut ParseContext::default();
+
+        let err = parse_capture(quote! { #(items*[,]: Ident = x()) }, ctx).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "default values are only supported on single or '?' captures"
+        );
+
+        let err = parse_capture(quote! { #(s?: span(#(a: Ident)) = x()) }, ctx).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "default values are only supported on type captures"
+        );
+
+        let err = parse_capture(quote! { #(ty?: Type =) }, ctx).unwrap_err();
+        assert_eq!(err.to_string(), "expected a default value after '='");
+    }
+
+    #[test]
+    fn test_parse_unordered() {
+        let ctx = &mut ParseContext::default();
+
+        let capture = parse_capture(
+            quote! { #(&[,]: name = #(name: LitStr) | #(?: rename = #(to: Ident)) | #(skip?: skip = bool)) },
+            ctx,
+        )
+        .unwrap();
+        assert_anonymous(&capture);
+        let MatcherKind::Unordered { separator, members } = &capture.matcher.kind else {
+            panic!("expected an unordered group");
+        };
+        assert_eq!(separator.to_string(), ",");
+        let keys: Vec<_> = members.iter().map(|m| m.key.to_string()).collect();
+        assert_eq!(keys, ["name", "rename", "skip"]);
+        let optional: Vec<_> = members.iter().map(|m| m.optional).collect();
+        assert_eq!(optional, [false, true, true]);
+
+        // 成员中的捕获展开到当前层级，必需成员的字段不是 Option
+        let fields = capture.collect_captures();
+        let names: Vec<_> = fields.iter().map(|f| f.name.to_string()).collect();
+        assert_eq!(names, ["name", "to", "skip"]);
+        assert!(!fields[0].is_optional);
+        assert!(fields[1].is_optional);
+        assert_eq!(fields[2].public_ty(), parse_quote!(bool));
+    }
+
+    #[test]
+    fn test_parse_unordered_error() {
+        let ctx = &mut ParseContext::default();
+
+        let err = parse_capture(quote! { #(&[,]: #(name: LitStr) | skip) }, ctx).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "each member of an unordered group must start with a literal key, like 'name = #(name: LitStr)' or '#(skip?: skip)'"
+        );
+
+        let err = parse_capture(quote! { #(&[,]: skip | #(?: skip)) }, ctx).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "duplicate key `skip` in the unordered group"
+        );
+
+        let err = parse_capture(quote! { #(&[]: skip) }, ctx).unwrap_err();
+        assert_eq!(err.to_string(), "expected '[<separator>]' like '[,]'");
+    }
+
     #[test]
     fn test_parse_inline() {
         let ctx = &mut ParseContext::default();
@@ -536,7 +1592,7 @@ mod tests {
         assert_eq!(variants.len(), 2);
 
         match &variants[0].0 {
-            EnumVariant::Type { ident, ty } => {
+            EnumVariant::Type { ident, ty, .. } => {
                 assert_eq!(ident, &parse_quote!(Type));
                 assert_eq!(ty, &parse_quote!(Type));
             }
@@ -544,7 +1600,7 @@ mod tests {
         }
 
         match &variants[1].0 {
-            EnumVariant::Type { ident, ty } => {
+            EnumVariant::Type { ident, ty, .. } => {
                 assert_eq!(ident, &parse_quote!(Ident));
                 assert_eq!(ty, &parse_quote!(syn::Ident));
             }
@@ -566,14 +1622,14 @@ mod tests {
         assert_eq!(enum_name, expect_enum_name);
         assert_eq!(variants.len(), 2);
         match &variants[0].0 {
-            EnumVariant::Type { ident, ty } => {
+            EnumVariant::Type { ident, ty, .. } => {
                 assert_eq!(ident, &parse_quote!(Ty));
                 assert_eq!(ty, &parse_quote!(Type));
             }
             _ => panic!("Expected EnumVariant::Type"),
         }
         match &variants[1].0 {
-            EnumVariant::Type { ident, ty } => {
+            EnumVariant::Type { ident, ty, .. } => {
                 assert_eq!(ident, &parse_quote!(Id));
                 assert_eq!(ty, &parse_quote!(Ident));
             }
@@ -611,12 +1667,16 @@ mod tests {
                             name: parse_quote!(id),
                             is_inline: false,
                             is_optional: false,
+                            default: None,
+                            attrs: Vec::new(),
                         },
                         FieldDef {
                             ty: parse_quote!(Type),
                             name: parse_quote!(ty),
                             is_inline: false,
                             is_optional: false,
+                            default: None,
+                            attrs: Vec::new(),
                         },
                     ]
                 );
@@ -641,12 +1701,16 @@ mod tests {
                             name: parse_quote!(name),
                             is_inline: false,
                             is_optional: false,
+                            default: None,
+                            attrs: Vec::new(),
                         },
                         FieldDef {
                             ty: parse_quote!(Expr),
                             name: parse_quote!(default),
                             is_inline: false,
                             is_optional: false,
+                            default: None,
+                            attrs: Vec::new(),
                         },
                     ]
                 );
@@ -685,12 +1749,16 @@ mod tests {
                             name: parse_quote!(_0),
                             is_inline: true,
                             is_optional: false,
+                            default: None,
+                            attrs: Vec::new(),
                         },
                         FieldDef {
                             ty: parse_quote!(Type),
                             name: parse_quote!(_1),
                             is_inline: true,
                             is_optional: false,
+                            default: None,
+                            attrs: Vec::new(),
                         },
                     ]
                 );
@@ -714,12 +1782,16 @@ mod tests {
                             name: parse_quote!(_2),
                             is_inline: true,
                             is_optional: false,
+                            default: None,
+                            attrs: Vec::new(),
                         },
                         FieldDef {
                             ty: parse_quote!(Expr),
                             name: parse_quote!(_3),
                             is_inline: true,
                             is_optional: false,
+                            default: None,
+                            attrs: Vec::new(),
                         },
                     ]
                 );
@@ -748,7 +1820,7 @@ mod tests {
         assert_eq!(variants.len(), 4);
 
         match &variants[0].0 {
-            EnumVariant::Type { ident, ty } => {
+            EnumVariant::Type { ident, ty, .. } => {
                 assert_eq!(ident, &parse_quote!(Ident));
                 assert_eq!(ty, &parse_quote!(syn::Ident));
             }
@@ -756,7 +1828,7 @@ mod tests {
         }
 
         match &variants[1].0 {
-            EnumVariant::Type { ident, ty } => {
+            EnumVariant::Type { ident, ty, .. } => {
                 assert_eq!(ident, &parse_quote!(Ty));
                 assert_eq!(ty, &parse_quote!(Type));
             }
@@ -780,12 +1852,16 @@ mod tests {
                             name: parse_quote!(id),
                             is_inline: false,
                             is_optional: false,
+                            default: None,
+                            attrs: Vec::new(),
                         },
                         FieldDef {
                             ty: parse_quote!(Type),
                             name: parse_quote!(ty),
                             is_inline: false,
                             is_optional: false,
+                            default: None,
+                            attrs: Vec::new(),
                         },
                     ]
                 );
@@ -810,12 +1886,16 @@ mod tests {
                             name: parse_quote!(_0),
                             is_inline: true,
                             is_optional: false,
+                            default: None,
+                            attrs: Vec::new(),
                         },
                         FieldDef {
                             ty: parse_quote!(Expr),
                             name: parse_quote!(_1),
                             is_inline: true,
                             is_optional: false,
+                            default: None,
+                            attrs: Vec::new(),
                         },
                     ]
                 );
@@ -844,6 +1924,12 @@ mod tests {
         let tokens_iter = compiler.compile_capture(&spec_iter);
         // 生成的代码应该包含 parse_terminated
         assert!(tokens_iter.to_string().contains("parse_terminated"));
+
+        // 带末尾分隔符策略时逐个解析元素与分隔符
+        let spec_strict: Capture = parse_capture(quote!(#(x*[,!]: Ident)), ctx).unwrap();
+        let tokens_strict = compiler.compile_capture(&spec_strict);
+        assert!(tokens_strict.to_string().contains("push_punct"));
+        assert!(!tokens_strict.to_string().contains("parse_terminated"));
     }
 
     #[test]
@@ -871,4 +1957,77 @@ mod tests {
         // 具体的错误信息取决于 bracketed! 空内容的判定
         assert!(result.unwrap_err().to_string().contains("expected"));
     }
+
+    fn pattern_children(input: TokenStream) -> Result<Vec<Pattern>> {
+        let pattern = Pattern::parse.parse2(input)?;
+        let PatternKind::Group { children, .. } = pattern.kind else {
+            panic!("Expected Group, got {:?}", pattern.kind);
+        };
+        Ok(children)
+    }
+
+    #[test]
+    fn test_parse_semantic_info() {
+        let children = pattern_children(quote!(
+            #[expect("a snake_case identifier", example = "my_fn")]
+            #(name: Ident) as "function name"
+            #(ty: Type) as #(alias: Ident)
+        ))
+        .unwrap();
+        assert_eq!(
+            children[0].meta,
+            Some(SemanticInfo {
+                name: Some("function name".to_string()),
+                expectation: Some("a snake_case identifier".to_string()),
+                example: Some("my_fn".to_string()),
+                custom_error: None,
+            })
+        );
+        // 后面不是字符串时 `as` 仍是字面量
+        assert_eq!(children[1].meta, None);
+        assert!(matches!(&children[2].kind, PatternKind::Literal(Keyword::Rust(kw)) if kw == "as"));
+
+        // 没有字符串参数的 `#[expect(...)]` 按字面量匹配
+        let children = pattern_children(quote!(#[expect(dead_code)] #(item: Item))).unwrap();
+        assert!(matches!(&children[0].kind, PatternKind::Literal(Keyword::Rust(kw)) if kw == "#"));
+
+        // 嵌套匹配器中同样可用
+        let ctx = &mut ParseContext::default();
+        let matcher = parse_capture_matcher(quote!(#(arg: Ident) as "argument"), ctx).unwrap();
+        let MatcherKind::Nested(children) = matcher.kind else {
+            panic!("Expected Nested, got {:?}", matcher.kind);
+        };
+        assert_eq!(
+            children[0].meta.as_ref().and_then(|m| m.name.as_deref()),
+            Some("argument")
+        );
+    }
+
+    #[test]
+    fn test_parse_semantic_info_error() {
+        let err = pattern_children(quote!(#[expect("x", hint = "y")] #(name: Ident))).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "unknown `expect` option `hint`, expected `example` or `error`"
+        );
+        let err = pattern_children(quote!(
+            #[expect("x")]
+            name
+        ))
+        .unwrap_err();
+        assert_eq!(err.to_string(), "expected a capture after `#[expect(...)]`");
+    }
+
+    #[test]
+    fn test_describe_blocks() {
+        let ctx = &mut ParseContext::default();
+        let mut describe_input = |input: TokenStream| describe(parse_capture(input, ctx).unwrap());
+        assert_eq!(describe_input(quote!(#(args*[,]: Expr))), "<Expr>, ...");
+        assert_eq!(describe_input(quote!(#(stmts+: Stmt))), "<Stmt> ...+");
+        assert_eq!(describe_input(quote!(#(?: -> #(ret: Type)))), "-> <Type>?");
+        assert_eq!(
+            describe_input(quote!(#(pair{2}[;]: #(k: Ident) = #(v: Expr)))),
+            "<Ident> = <Expr>; ...{2}"
+        );
+    }
 }
@@ -0,0 +1,316 @@
+//! ### First
+//! <div class="doc-cn">
+//!
+//! 此处定义了模式的 FIRST 集合，即模式可能以哪些 token 开头
+//! 多态枚举与选择据此先 `peek` 再解析，只在开头可能相同的分支之间回退到 fork 逐个尝试
+//!
+//! </div>
+use proc_macro2::{Delimiter, TokenStream};
+use quote::quote;
+use syn::Type;
+
+use crate::ast::{
+    capture::{Capture, EnumVariant, Matcher, MatcherKind, Quantity, RecordKind},
+    keyword::{Keyword, KeywordMap},
+    node::{Pattern, PatternKind},
+};
+
+#[derive(Clone)]
+#[cfg_attr(any(feature = "extra-traits", test), derive(Debug, PartialEq))]
+pub enum First {
+    /// 字面量 (e.g. `fn`, `->`, 自定义关键字)
+    Keyword(Keyword),
+    /// `syn::Ident`（不包括 Rust 关键字）
+    Ident,
+    /// `syn::Lifetime`
+    Lifetime,
+    /// `syn::Lit` 或具体的字面量类型 (e.g. `LitStr`)
+    Lit(Option<String>),
+    /// 分组 `(...)`、`[...]`、`{...}`
+    Delimiter(Delimiter),
+    /// 无法在展开时确定（可选开头、自定义解析函数、用户类型等）
+    Unknown,
+}
+
+impl First {
+    pub fn of_variant(variant: &EnumVariant) -> Self {
+        match variant {
+            EnumVariant::Type {
+                ty, parser: None, ..
+            } => First::of_type(ty),
+            EnumVariant::Type { .. } => First::Unknown,
+            EnumVariant::Capture { pattern, .. } => First::of_pattern(pattern),
+        }
+    }
+
+    pub fn of_pattern(pattern: &Pattern) -> Self {
+        match &pattern.kind {
+            PatternKind::Literal(keyword) => First::Keyword(keyword.clone()),
+            PatternKind::Group {
+                delimiter: Delimiter::None,
+                children,
+            } => First::of_patterns(children),
+            PatternKind::Group { delimiter, .. } => First::Delimiter(*delimiter),
+            PatternKind::Capture(capture) => First::of_capture(capture),
+        }
+    }
+
+    fn of_patterns(patterns: &[Pattern]) -> Self {
+        match patterns.first() {
+            Some(pattern) => First::of_pattern(pattern),
+            None => First::Unknown,
+        }
+    }
+
+    fn of_capture(capture: &Capture) -> Self {
+        // 可以为空的捕获无法确定开头
+        match capture.quantity {
+            Quantity::One => {}
+            Quantity::Bounded { min, .. } if min > 0 => {}
+            _ => return First::Unknown,
+        }
+        First::of_matcher(&capture.matcher)
+    }
+
+    /// 匹配器本身的开头，不考虑数量限定
+    pub fn of_matcher(matcher: &Matcher) -> Self {
+        match &matcher.kind {
+            MatcherKind::SynType(ty) => First::of_type(ty),
+            MatcherKind::Nested(children) | MatcherKind::Named { children, .. } => {
+                First::of_patterns(children)
+            }
+            MatcherKind::Record {
+                kind: RecordKind::Delimiter(delimiter),
+                ..
+            } => First::Delimiter(*delimiter),
+            MatcherKind::Record { children, .. } => First::of_patterns(children),
+            _ => First::Unknown,
+        }
+    }
+
+    fn of_type(ty: &Type) -> Self {
+        match ty {
+            Type::Macro(ty) if ty.mac.path.is_ident("Token") => {
+                let text = ty.mac.tokens.to_string().replace(' ', "");
+                First::Keyword(Keyword::Rust(text))
+            }
+            Type::Path(ty) if ty.qself.is_none() => {
+                let Some(segment) = ty.path.segments.last() else {
+                    return First::Unknown;
+                };
+                if !segment.arguments.is_none() {
+                    return First::Unknown;
+                }
+                match segment.ident.to_string().as_str() {
+                    "Ident" => First::Ident,
+                    "Lifetime" => First::Lifetime,
+                    "Lit" => First::Lit(None),
+                    lit @ ("LitStr" | "LitByteStr" | "LitCStr" | "LitByte" | "LitChar"
+                    | "LitInt" | "LitFloat" | "LitBool") => First::Lit(Some(lit.to_string())),
+                    _ => First::Unknown,
+                }
+            }
+            Type::Group(ty) => First::of_type(&ty.elem),
+            Type::Paren(ty) => First::of_type(&ty.elem),
+            _ => First::Unknown,
+        }
+    }
+
+    /// 两个集合是否可能匹配同一个 token
+    pub fn overlaps(&self, other: &Self) -> bool {
+        match (self, other) {
+            (First::Unknown, _) | (_, First::Unknown) => true,
+            (First::Keyword(a), First::Keyword(b)) => {
+                let (a, b) = (a.to_string(), b.to_string());
+                if is_word(&a) || is_word(&b) {
+                    a == b
+                } else {
+                    // `-` 也能在 `->` 上 peek 成功
+                    a.starts_with(&b) || b.starts_with(&a)
+                }
+            }
+            (First::Keyword(keyword), First::Ident) | (First::Ident, First::Keyword(keyword)) => {
+                let word = keyword.to_string();
+                is_word(&word) && !is_reserved(&word)
+            }
+            (First::Keyword(keyword), First::Lit(lit))
+            | (First::Lit(lit), First::Keyword(keyword)) => match keyword.to_string().as_str() {
+                "true" | "false" => lit.as_deref().is_none_or(|lit| lit == "LitBool"),
+                // `Lit`、`LitInt`、`LitFloat` 接受负数字面量 (e.g. `-1`)
+                "-" => lit
+                    .as_deref()
+                    .is_none_or(|lit| matches!(lit, "LitInt" | "LitFloat")),
+                _ => false,
+            },
+            (First::Ident, First::Ident) | (First::Lifetime, First::Lifetime) => true,
+            (First::Lit(a), First::Lit(b)) => a.is_none() || b.is_none() || a == b,
+            (First::Delimiter(a), First::Delimiter(b)) => a == b,
+            // `Ident` 不匹配 `true`/`false`，`Lifetime` 与分组只以各自的 token 开头；
+            // 逐一列出，新增的开头类型必须在此说明是否重叠
+            (First::Keyword(_), First::Lifetime | First::Delimiter(_))
+            | (First::Ident | First::Lifetime | First::Lit(_) | First::Delimiter(_), _) => false,
+        }
+    }
+
+    /// 生成 `peek` 表达式；自定义关键字的定义写入 `keywords`
+    pub fn peek(&self, keywords: &mut KeywordMap) -> Option<TokenStream> {
+        let token = match self {
+            First::Keyword(keyword) => {
+                keyword.define(keywords);
+                quote!(#keyword)
+            }
+            First::Ident => quote!(::syn::Ident),
+            First::Lifetime => quote!(::syn::Lifetime),
+            First::Lit(None) => quote!(::syn::Lit),
+            First::Lit(Some(lit)) => {
+                let lit = quote::format_ident!("{}", lit);
+                quote!(::syn::#lit)
+            }
+            First::Delimiter(Delimiter::Parenthesis) => quote!(::syn::token::Paren),
+            First::Delimiter(Delimiter::Brace) => quote!(::syn::token::Brace),
+            First::Delimiter(Delimiter::Bracket) => quote!(::syn::token::Bracket),
+            First::Delimiter(Delimiter::None) | First::Unknown => return None,
+        };
+        Some(quote!(input.peek(#token)))
+    }
+}
+
+fn is_word(text: &str) -> bool {
+    text.chars()
+        .next()
+        .is_some_and(|c| c.is_alphabetic() || c == '_')
+}
+
+/// `syn::Ident` 不会匹配的关键字（`union`、`default` 等上下文关键字除外）
+fn is_reserved(word: &str) -> bool {
+    matches!(
+        word,
+        "_" | "abstract"
+            | "as"
+            | "async"
+            | "await"
+            | "become"
+            | "box"
+            | "break"
+            | "const"
+            | "continue"
+            | "crate"
+            | "do"
+            | "dyn"
+            | "else"
+            | "enum"
+            | "extern"
+            | "false"
+            | "final"
+            | "fn"
+            | "for"
+            | "if"
+            | "impl"
+            | "in"
+            | "let"
+            | "loop"
+            | "macro"
+            | "match"
+            | "mod"
+            | "move"
+            | "mut"
+            | "override"
+            | "priv"
+            | "pub"
+            | "ref"
+            | "return"
+            | "Self"
+            | "self"
+            | "static"
+            | "struct"
+            | "super"
+            | "trait"
+            | "true"
+            | "try"
+            | "type"
+            | "typeof"
+            | "unsafe"
+            | "unsized"
+            | "use"
+            | "virtual"
+            | "where"
+            | "while"
+            | "yield"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use quote::quote;
+    use syn::parse2;
+
+    use crate::ast::input::DefineInput;
+
+    use super::*;
+
+    fn variant_firsts(input: TokenStream) -> Vec<First> {
+        let input: DefineInput = parse2(input).unwrap();
+        let PatternKind::Group { children, .. } = &input.patterns.kind else {
+            panic!("expected group");
+        };
+        let PatternKind::Capture(capture) = &children[0].kind else {
+            panic!("expected capture");
+        };
+        let MatcherKind::Enum { variants, .. } = &capture.matcher.kind else {
+            panic!("expected enum");
+        };
+        variants.iter().map(|(v, _)| First::of_variant(v)).collect()
+    }
+
+    #[test]
+    fn test_first_of_variants() {
+        let firsts = variant_firsts(quote! {
+            Item: #(item: Kind {
+                Fn: fn #(name: Ident),
+                Name: Ident,
+                Str: LitStr,
+                Arrow: Token![->],
+                Group: ( #(inner: Expr) ),
+                Vis: #(?: pub) unit,
+                Many: #(names+[,]: Ident) ;,
+                Other: Expr,
+            })
+        });
+        assert_eq!(
+            firsts,
+            [
+                First::Keyword(Keyword::Rust("fn".into())),
+                First::Ident,
+                First::Lit(Some("LitStr".into())),
+                First::Keyword(Keyword::Rust("->".into())),
+                First::Delimiter(Delimiter::Parenthesis),
+                First::Unknown,
+                First::Ident,
+                First::Unknown,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_first_overlaps() {
+        let keyword = |k: &str| First::Keyword(Keyword::Rust(k.into()));
+        // Rust 关键字不会被 `Ident` 匹配，上下文关键字会
+        assert!(!keyword("fn").overlaps(&First::Ident));
+        assert!(keyword("union").overlaps(&First::Ident));
+        assert!(!keyword("fn").overlaps(&keyword("struct")));
+        assert!(keyword("-").overlaps(&keyword("->")));
+        assert!(!keyword("->").overlaps(&keyword("=>")));
+        assert!(keyword("true").overlaps(&First::Lit(None)));
+        assert!(!keyword("true").overlaps(&First::Lit(Some("LitStr".into()))));
+        // 负数字面量以 `-` 开头
+        assert!(keyword("-").overlaps(&First::Lit(None)));
+        assert!(First::Lit(Some("LitInt".into())).overlaps(&keyword("-")));
+        assert!(keyword("-").overlaps(&First::Lit(Some("LitFloat".into()))));
+        assert!(!keyword("-").overlaps(&First::Lit(Some("LitStr".into()))));
+        assert!(!keyword("->").overlaps(&First::Lit(None)));
+        assert!(!First::Lit(Some("LitStr".into())).overlaps(&First::Lit(Some("LitInt".into()))));
+        assert!(First::Lit(None).overlaps(&First::Lit(Some("LitInt".into()))));
+        assert!(!First::Delimiter(Delimiter::Brace).overlaps(&First::Delimiter(Delimiter::Bracket)));
+        assert!(First::Unknown.overlaps(&First::Lifetime));
+    }
+}
@@ -1,13 +1,15 @@
 use proc_macro2::TokenStream;
-use syn::{Attribute, Ident, Local, Token, Visibility};
+use syn::{token, Attribute, Expr, Generics, Ident, Local, Token, Visibility};
 
 use crate::ast::node::Pattern;
 
 #[cfg_attr(any(feature = "extra-traits", test), derive(Debug))]
 pub struct BindInput {
     pub local: Local,
-    pub input: Ident,
-    pub _arrow: Token![->],
+    pub input: Expr,
+    pub _arrow: Option<Token![->]>,
+    /// `(input => ...)`：只消费 `ParseStream` 的前缀，剩余部分留给后续代码
+    pub fat_arrow: Option<Token![=>]>,
     pub patterns: Pattern,
     pub suffix: TokenStream,
 }
@@ -16,17 +18,35 @@ pub struct BindInput {
 pub struct DefineInput {
     pub attrs: Vec<Attribute>,
     pub visibility: Visibility,
+    /// 枚举形式 `enum Name { ... }`，此时 `patterns` 为单个匿名的多态枚举捕获
+    pub enum_token: Option<Token![enum]>,
     pub name: Ident,
-    pub _colon: Token![:],
+    pub generics: Generics,
+    pub _colon: Option<Token![:]>,
     pub patterns: Pattern,
 }
 
+#[cfg_attr(any(feature = "extra-traits", test), derive(Debug))]
+pub struct MatchInput {
+    pub input: Expr,
+    pub _brace: token::Brace,
+    pub arms: Vec<MatchArm>,
+}
+
+#[cfg_attr(any(feature = "extra-traits", test), derive(Debug))]
+pub struct MatchArm {
+    /// `None` 为通配分支 `_`
+    pub patterns: Option<Pattern>,
+    pub _fat_arrow: Token![=>],
+    pub body: Expr,
+}
+
 #[cfg(test)]
 mod tests {
     use crate::codegen::logic::Compiler;
 
     use super::*;
-    use quote::quote;
+    use quote::{quote, ToTokens};
 
     use syn::parse2;
 
@@ -38,7 +58,7 @@ mod tests {
         let stream = quote! { let res = (my_tokens -> name: Ident); };
         let result: BindInput = parse2(stream).unwrap();
 
-        assert_eq!(result.input.to_string(), "my_tokens");
+        assert_eq!(result.input.to_token_stream().to_string(), "my_tokens");
         // 验证 patterns 能够成功解析 (PatternList 的具体解析由它自己的测试保证)
         // 这里只要不 panic 且结构存在即可
     }
@@ -51,7 +71,7 @@ mod tests {
         };
         let result: BindInput = parse2(stream).unwrap();
 
-        assert_eq!(result.input.to_string(), "input_stream");
+        assert_eq!(result.input.to_token_stream().to_string(), "input_stream");
     }
 
     #[test]
@@ -73,6 +93,25 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_parse_expr_input() {
+        // 输入可以是任意表达式
+        let stream = quote! { let res = (a.b() + c -> #(x: Ident)); };
+        let result: BindInput = parse2(stream).unwrap();
+        assert!(matches!(result.input, Expr::Binary(_)));
+        assert!(result.fat_arrow.is_none());
+
+        let stream = quote! { let res = (input => #(x: Ident)); };
+        let result: BindInput = parse2(stream).unwrap();
+        assert!(result.fat_arrow.is_some());
+
+        let result = parse2::<BindInput>(quote! { let res = (=> #(x: Ident)); });
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "expected an input expression"
+        );
+    }
+
     // --- 2. 代码生成测试 (impl ToTokens) ---
 
     #[test]
@@ -116,6 +155,23 @@ mod tests {
         assert!(output_str.trim().ends_with("} ? ;"));
     }
 
+    #[test]
+    fn test_to_tokens_input_modes() {
+        let mut compiler = Compiler::new();
+        let stream = quote! { let res = (a + b -> #(val: Ident)); };
+        let capture_input: BindInput = parse2(stream).unwrap();
+        let output = compiler.compile_capture_input(&capture_input).to_string();
+        assert!(output.contains("(parser , (a + b) . into ())"));
+
+        // `=>` 直接调用解析闭包，不经过 parse2
+        let mut compiler = Compiler::new();
+        let stream = quote! { let res = (input => #(val: Ident)); };
+        let capture_input: BindInput = parse2(stream).unwrap();
+        let output = compiler.compile_capture_input(&capture_input).to_string();
+        assert!(output.contains("parser (input)"));
+        assert!(!output.contains("parse2"));
+    }
+
     // 集成测试模拟：检查生成的逻辑是否包含捕获组初始化
     #[test]
     fn test_generated_logic_contains_initialization() {
@@ -132,4 +188,93 @@ mod tests {
         // 检查最终结果是否返回 Output 结构体
         assert!(output.contains(":: std :: result :: Result :: Ok"));
     }
+
+    #[test]
+    fn test_parse_define_generics() {
+        let stream = quote! { Pairs<K, V: Clone> where K: Clone: #(key: K) => #(value: V) };
+        let input: DefineInput = parse2(stream).unwrap();
+        assert_eq!(input.generics.params.len(), 2);
+        assert!(input.generics.where_clause.is_some());
+    }
+
+    #[test]
+    fn test_define_generics_threading() {
+        let mut compiler = Compiler::new();
+        let stream = quote! { Listed<T: Parse, U>: #(items*[,]: #(item: T)) #(last: U) };
+        let input: DefineInput = parse2(stream).unwrap();
+        let output = compiler.compile_define_input(&input).to_string();
+
+        assert!(output.contains("struct Listed < T : Parse , U >"));
+        assert!(
+            output.contains("impl < T : Parse , U > :: syn :: parse :: Parse for Listed < T , U >")
+        );
+        // 嵌套结构只声明其字段用到的参数
+        assert!(output.contains("struct items_Item < T : Parse >"));
+    }
+
+    #[test]
+    fn test_define_unused_generics() {
+        let mut compiler = Compiler::new();
+        let stream = quote! { Marked<'a, T: Parse, const N: usize>: #(name: Ident) #(T) };
+        let input: DefineInput = parse2(stream).unwrap();
+        let output = compiler.compile_define_input(&input).to_string();
+
+        // 未被字段使用的参数保留在结构体上，并由标记字段占用
+        assert!(output.contains("struct Marked < 'a , T : Parse , const N : usize >"));
+        assert!(output.contains(
+            "pub _marker : :: std :: marker :: PhantomData < (& 'a () , fn () -> T ,) >"
+        ));
+        assert!(output.contains("_marker : :: std :: marker :: PhantomData ,"));
+    }
+
+    #[test]
+    fn test_define_enum_form() {
+        let stream = quote! { pub enum Item { Fn: fn #(name: Ident), Ident } };
+        let input: DefineInput = parse2(stream).unwrap();
+        assert!(input.enum_token.is_some());
+        let output = Compiler::new().compile_define_input(&input).to_string();
+        assert!(output.contains("pub enum Item"));
+        assert!(output.contains(":: syn :: parse :: Parse for Item"));
+
+        // 枚举形式不支持泛型参数
+        let result = parse2::<DefineInput>(quote! { enum Item<T> { A: T } });
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "generic parameters are not supported on `define!` enums"
+        );
+    }
+
+    #[test]
+    fn test_parse_match_input() {
+        let stream = quote! {
+            input {
+                fn #(name: Ident) => name,
+                struct #(name: Ident) { #(fields*[,]: Ident) } => { name }
+                _ => unreachable!(),
+            }
+        };
+        let input: MatchInput = parse2(stream).unwrap();
+        assert_eq!(input.arms.len(), 3);
+        assert!(input.arms[2].patterns.is_none());
+
+        let output = Compiler::compile_match_input(&input).to_string();
+        assert_eq!(
+            output
+                .matches(":: syn :: parse :: Parser :: parse2")
+                .count(),
+            2
+        );
+
+        // `_` 之后不能再有分支
+        let stream = quote! { input { _ => 0, fn => 1 } };
+        assert_eq!(
+            parse2::<MatchInput>(stream).unwrap_err().to_string(),
+            "the `_` arm must be the last arm"
+        );
+        let stream = quote! { input { fn => 0 struct => 1 } };
+        assert_eq!(
+            parse2::<MatchInput>(stream).unwrap_err().to_string(),
+            "expected `,` after the arm body"
+        );
+    }
 }
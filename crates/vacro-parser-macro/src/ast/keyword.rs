@@ -1,4 +1,4 @@
-use std::{collections::HashMap, fmt};
+use std::{collections::BTreeMap, fmt};
 
 use proc_macro2::{Punct, TokenStream};
 use quote::{format_ident, quote, ToTokens, TokenStreamExt};
@@ -221,11 +221,11 @@ impl ToTokens for Keyword {
 
 #[derive(Clone)]
 #[cfg_attr(any(feature = "extra-traits", test), derive(Debug))]
-pub struct KeywordMap(pub HashMap<String, TokenStream>);
+pub struct KeywordMap(pub BTreeMap<String, TokenStream>);
 
 impl KeywordMap {
     pub fn new() -> Self {
-        KeywordMap(HashMap::new())
+        KeywordMap(BTreeMap::new())
     }
 }
 
@@ -3,10 +3,10 @@
 //!
 //! 此处定义了`SemanticInfo`
 //! `SemanticInfo`是用于生成更友好的错误信息的
+//! 由捕获前的 `#[expect("描述", example = "...")]` 与捕获后的 `as "名称"` 提供
 //!
 //! </div>
-#[allow(unused)]
-#[derive(Clone)]
+#[derive(Clone, Default)]
 #[cfg_attr(any(feature = "extra-traits", test), derive(Debug, PartialEq))]
 pub struct SemanticInfo {
     /// 规则名称，用于报错。例如 "Function Name"
@@ -15,6 +15,6 @@ pub struct SemanticInfo {
     pub expectation: Option<String>,
     /// 示例代码。例如 "like: `my_fn`"
     pub example: Option<String>,
-    /// 自定义错误模板
+    /// 自定义错误模板，`{found}` 会替换为实际遇到的 token
     pub custom_error: Option<String>,
 }
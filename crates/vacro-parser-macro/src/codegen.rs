@@ -1,3 +1,4 @@
+pub mod grammar;
 pub mod logic;
 pub mod output;
 pub mod utils;
@@ -0,0 +1,386 @@
+use std::borrow::Cow;
+
+use proc_macro2::{Delimiter, Ident, TokenStream, TokenTree};
+use quote::{quote, ToTokens};
+use syn::Visibility;
+use vacro_grammar::{Grammar, Member, Node, Variant};
+
+use crate::{
+    ast::{
+        capture::{Binder, Capture, EnumVariant, Matcher, MatcherKind, Quantity, RecordKind},
+        node::{Pattern, PatternKind},
+    },
+    utils::resolve_crate_root,
+};
+
+/// 由模式构建语法描述，用于生成 `grammar()` 方法及文档中的铁路图
+pub fn build_grammar(name: &str, pattern: &Pattern) -> Grammar {
+    let mut items = build_pattern(pattern);
+    // `Self` 指向语法自身，统一写作语法名，避免出现未定义的非终结符
+    rename_self(&mut items, name);
+    Grammar {
+        name: name.to_string().into(),
+        items: items.into(),
+    }
+}
+
+fn rename_self(nodes: &mut [Node], name: &str) {
+    for node in nodes {
+        match node {
+            Node::Literal { .. } => {}
+            Node::Capture { ty, .. } => *ty = replace_self(ty, name).into(),
+            Node::Nested { items, .. }
+            | Node::Group { items, .. }
+            | Node::Optional { items }
+            | Node::Repeat { items, .. }
+            | Node::Lookahead { items, .. } => rename_self(items.to_mut(), name),
+            Node::Choice { ty, variants, .. } => {
+                if let Some(ty) = ty {
+                    *ty = replace_self(ty, name).into();
+                }
+                for variant in variants.to_mut() {
+                    rename_self(variant.items.to_mut(), name);
+                }
+            }
+            Node::Unordered { members, .. } => {
+                for member in members.to_mut() {
+                    rename_self(member.items.to_mut(), name);
+                }
+            }
+        }
+    }
+}
+
+/// 替换类型中的 `Self` 标识符 (e.g. `Vec<Self>` -> `Vec<Tree>`)
+fn replace_self(ty: &str, name: &str) -> String {
+    fn replace(tokens: TokenStream, name: &str) -> TokenStream {
+        tokens
+            .into_iter()
+            .map(|tt| match tt {
+                TokenTree::Ident(ident) if ident == "Self" => {
+                    TokenTree::Ident(Ident::new(name, ident.span()))
+                }
+                TokenTree::Group(group) => {
+                    let mut replaced =
+                        proc_macro2::Group::new(group.delimiter(), replace(group.stream(), name));
+                    replaced.set_span(group.span());
+                    TokenTree::Group(replaced)
+                }
+                tt => tt,
+            })
+            .collect()
+    }
+    match ty.parse::<TokenStream>() {
+        Ok(tokens) => type_string(replace(tokens, name)),
+        Err(_) => ty.to_string(),
+    }
+}
+
+/// 模式的单行语法描述 (e.g. `fn <Ident> ( ... )`)，即 `Grammar` 的 `Display` 输出，用于报错
+pub fn describe_pattern(pattern: &Pattern) -> String {
+    describe(build_pattern(pattern))
+}
+
+/// 以 `a | b` 的形式描述选择的各个分支，用于文档与报错
+pub fn describe_alternation(variants: &[(EnumVariant, Matcher)]) -> String {
+    describe(vec![Node::Choice {
+        name: None,
+        ty: None,
+        variants: build_variants(variants).into(),
+    }])
+}
+
+fn describe(items: Vec<Node>) -> String {
+    Grammar {
+        name: Cow::Borrowed(""),
+        items: items.into(),
+    }
+    .to_string()
+}
+
+/// 生成返回语法描述 (`vacro_parser::grammar::Grammar`) 的 `grammar()` 方法
+pub fn generate_grammar_fn(grammar: &Grammar, visibility: &Visibility) -> TokenStream {
+    let path = {
+        let root = resolve_crate_root();
+        quote!(#root::grammar)
+    };
+    let printer = GrammarPrinter { path: &path };
+    let name = printer.string(&grammar.name);
+    let items = printer.nodes(&grammar.items);
+    quote! {
+        /// The grammar accepted by this struct's `Parse` implementation.
+        #[allow(dead_code)]
+        #visibility const fn grammar() -> #path::Grammar {
+            #path::Grammar {
+                name: #name,
+                items: #items,
+            }
+        }
+    }
+}
+
+/// 在文档中嵌入铁路图 (`railroad-doc` feature)
+pub fn generate_railroad_doc(grammar: &Grammar) -> TokenStream {
+    if !cfg!(feature = "railroad-doc") {
+        return TokenStream::new();
+    }
+    let svg = grammar.to_railroad_svg();
+    quote! {
+        #[doc = ""]
+        #[doc = #svg]
+    }
+}
+
+fn build_patterns(patterns: &[Pattern]) -> Vec<Node> {
+    patterns.iter().flat_map(build_pattern).collect()
+}
+
+fn build_pattern(pattern: &Pattern) -> Vec<Node> {
+    match &pattern.kind {
+        PatternKind::Literal(keyword) => vec![Node::Literal {
+            text: keyword.to_string().into(),
+        }],
+        PatternKind::Group {
+            delimiter: Delimiter::None,
+            children,
+        } => build_patterns(children),
+        PatternKind::Group {
+            delimiter,
+            children,
+        } => vec![group(*delimiter, build_patterns(children))],
+        PatternKind::Capture(capture) => build_capture(capture),
+    }
+}
+
+fn build_capture(capture: &Capture) -> Vec<Node> {
+    let name = match &capture.binder {
+        Binder::Named(ident) => Some(ident.to_string()),
+        _ => None,
+    };
+    let items = build_matcher(&capture.matcher, name, capture.doc());
+    let separator = capture
+        .quantity
+        .separator()
+        .map(|sep| sep.to_string().into());
+    vec![match &capture.quantity {
+        Quantity::One => return items,
+        Quantity::Optional => Node::Optional {
+            items: items.into(),
+        },
+        Quantity::Bounded { min, max, .. } => Node::Repeat {
+            min: *min,
+            max: *max,
+            separator,
+            items: items.into(),
+        },
+        Quantity::Lookahead { negative } => Node::Lookahead {
+            negative: *negative,
+            items: items.into(),
+        },
+    }]
+}
+
+fn build_matcher(matcher: &Matcher, name: Option<String>, doc: Option<String>) -> Vec<Node> {
+    let nested = |items: Vec<Node>| match &name {
+        // 匿名的嵌套捕获直接展开
+        None => items,
+        Some(name) => vec![Node::Nested {
+            name: Some(name.clone().into()),
+            doc: doc.clone().map(Into::into),
+            items: items.into(),
+        }],
+    };
+    match &matcher.kind {
+        MatcherKind::SynType(ty) | MatcherKind::ParseWith { ty, .. } => vec![Node::Capture {
+            name: name.clone().map(Into::into),
+            ty: type_string(ty).into(),
+            doc: doc.clone().map(Into::into),
+        }],
+        MatcherKind::Nested(children) | MatcherKind::Named { children, .. } => {
+            nested(build_patterns(children))
+        }
+        MatcherKind::Record {
+            kind: RecordKind::Delimiter(delimiter),
+            children,
+        } => vec![group(*delimiter, build_patterns(children))],
+        MatcherKind::Record { children, .. } => nested(build_patterns(children)),
+        MatcherKind::Enum {
+            enum_name,
+            variants,
+        } => vec![Node::Choice {
+            name: name.map(Into::into),
+            ty: Some(type_string(enum_name).into()),
+            variants: build_variants(variants).into(),
+        }],
+        MatcherKind::Alternation(variants, _) => vec![Node::Choice {
+            name: name.map(Into::into),
+            ty: None,
+            variants: build_variants(variants).into(),
+        }],
+        MatcherKind::Unordered { separator, members } => vec![Node::Unordered {
+            separator: separator.to_string().into(),
+            members: members
+                .iter()
+                .map(|member| Member {
+                    key: member.key.to_string().into(),
+                    optional: member.optional,
+                    items: build_patterns(&member.inner).into(),
+                })
+                .collect(),
+        }],
+    }
+}
+
+fn build_variants(variants: &[(EnumVariant, Matcher)]) -> Vec<Variant> {
+    variants
+        .iter()
+        .map(|(variant, _)| match variant {
+            EnumVariant::Type { ident, ty, .. } => Variant {
+                name: type_string(ident).into(),
+                items: vec![Node::Capture {
+                    name: None,
+                    ty: type_string(ty).into(),
+                    doc: None,
+                }]
+                .into(),
+            },
+            EnumVariant::Capture { ident, pattern, .. } => Variant {
+                name: type_string(ident).into(),
+                items: build_pattern(pattern).into(),
+            },
+        })
+        .collect()
+}
+
+fn group(delimiter: Delimiter, items: Vec<Node>) -> Node {
+    Node::Group {
+        delimiter: match delimiter {
+            Delimiter::Brace => vacro_grammar::Delimiter::Brace,
+            Delimiter::Bracket => vacro_grammar::Delimiter::Bracket,
+            _ => vacro_grammar::Delimiter::Parenthesis,
+        },
+        items: items.into(),
+    }
+}
+
+/// 与文档及报错一致，去掉类型中的空格 (e.g. `Vec<Attribute>`)
+fn type_string(ty: impl ToTokens) -> String {
+    ty.to_token_stream().to_string().replace(' ', "")
+}
+
+/// 将语法描述输出为运行时构造它的表达式
+struct GrammarPrinter<'a> {
+    path: &'a TokenStream,
+}
+
+impl GrammarPrinter<'_> {
+    fn nodes(&self, nodes: &[Node]) -> TokenStream {
+        let nodes = nodes.iter().map(|node| self.node(node));
+        quote!(::std::borrow::Cow::Borrowed(&[#(#nodes),*]))
+    }
+
+    fn node(&self, node: &Node) -> TokenStream {
+        let path = self.path;
+        match node {
+            Node::Literal { text } => {
+                let text = self.string(text);
+                quote!(#path::Node::Literal { text: #text })
+            }
+            Node::Capture { name, ty, doc } => {
+                let (name, ty, doc) = (self.option(name), self.string(ty), self.option(doc));
+                quote!(#path::Node::Capture { name: #name, ty: #ty, doc: #doc })
+            }
+            Node::Nested { name, doc, items } => {
+                let (name, doc, items) = (self.option(name), self.option(doc), self.nodes(items));
+                quote!(#path::Node::Nested { name: #name, doc: #doc, items: #items })
+            }
+            Node::Group { delimiter, items } => {
+                let delimiter = match delimiter {
+                    vacro_grammar::Delimiter::Parenthesis => quote!(Parenthesis),
+                    vacro_grammar::Delimiter::Brace => quote!(Brace),
+                    vacro_grammar::Delimiter::Bracket => quote!(Bracket),
+                };
+                let items = self.nodes(items);
+                quote! {
+                    #path::Node::Group {
+                        delimiter: #path::Delimiter::#delimiter,
+                        items: #items,
+                    }
+                }
+            }
+            Node::Optional { items } => {
+                let items = self.nodes(items);
+                quote!(#path::Node::Optional { items: #items })
+            }
+            Node::Repeat {
+                min,
+                max,
+                separator,
+                items,
+            } => {
+                let max = match max {
+                    Some(max) => quote!(::std::option::Option::Some(#max)),
+                    None => quote!(::std::option::Option::None),
+                };
+                let (separator, items) = (self.option(separator), self.nodes(items));
+                quote! {
+                    #path::Node::Repeat {
+                        min: #min,
+                        max: #max,
+                        separator: #separator,
+                        items: #items,
+                    }
+                }
+            }
+            Node::Lookahead { negative, items } => {
+                let items = self.nodes(items);
+                quote!(#path::Node::Lookahead { negative: #negative, items: #items })
+            }
+            Node::Choice { name, ty, variants } => {
+                let (name, ty) = (self.option(name), self.option(ty));
+                let variants = variants.iter().map(|variant| {
+                    let (name, items) = (self.string(&variant.name), self.nodes(&variant.items));
+                    quote!(#path::Variant { name: #name, items: #items })
+                });
+                quote! {
+                    #path::Node::Choice {
+                        name: #name,
+                        ty: #ty,
+                        variants: ::std::borrow::Cow::Borrowed(&[#(#variants),*]),
+                    }
+                }
+            }
+            Node::Unordered { separator, members } => {
+                let separator = self.string(separator);
+                let members = members.iter().map(|member| {
+                    let (key, optional, items) = (
+                        self.string(&member.key),
+                        member.optional,
+                        self.nodes(&member.items),
+                    );
+                    quote!(#path::Member { key: #key, optional: #optional, items: #items })
+                });
+                quote! {
+                    #path::Node::Unordered {
+                        separator: #separator,
+                        members: ::std::borrow::Cow::Borrowed(&[#(#members),*]),
+                    }
+                }
+            }
+        }
+    }
+
+    fn string(&self, value: &str) -> TokenStream {
+        quote!(::std::borrow::Cow::Borrowed(#value))
+    }
+
+    fn option(&self, value: &Option<Cow<'static, str>>) -> TokenStream {
+        match value {
+            Some(value) => {
+                let value = self.string(value);
+                quote!(::std::option::Option::Some(#value))
+            }
+            None => quote!(::std::option::Option::None),
+        }
+    }
+}
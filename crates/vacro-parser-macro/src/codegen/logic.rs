@@ -1,5 +1,3 @@
-use std::time::{SystemTime, UNIX_EPOCH};
-
 use quote::format_ident;
 use syn::{Attribute, Ident, Item};
 
@@ -7,6 +5,7 @@ mod capture;
 mod input;
 mod keyword;
 mod pattern;
+mod printer;
 
 pub struct Compiler {
     pub shared_definition: Vec<Item>,
@@ -17,13 +16,11 @@ pub struct Compiler {
 
 impl Compiler {
     pub fn new() -> Self {
-        let now = SystemTime::now();
-        let duration_since_epoch = now.duration_since(UNIX_EPOCH).expect("Time went backwards");
-        let timestamp = duration_since_epoch.as_secs();
         Self {
             shared_definition: vec![],
             scoped_definition: vec![],
-            target: format_ident!("_{timestamp}"),
+            // 由 `define!` 的结构体名或 `bind!` 的绑定模式替换
+            target: format_ident!("Anonymous"),
             derive_attrs: vec![],
         }
     }
@@ -4,12 +4,24 @@ use syn::{parse_quote, punctuated::Punctuated, token::Comma, Expr, Ident, LitInt
 
 use crate::{
     ast::{
-        capture::{Binder, Capture, EnumVariant, FieldDef, Matcher, MatcherKind, Quantity},
+        capture::{
+            alternation_item_ident, variants_generics, Binder, Capture, EnumVariant, FieldDef,
+            Matcher, MatcherKind, Member, NamedItem, Quantity, RecordKind, Separator, Trailing,
+        },
+        first::First,
+        keyword::KeywordMap,
         node::{Pattern, PatternKind},
     },
-    codegen::{logic::Compiler, output::generate_output},
+    codegen::{
+        grammar::describe_pattern,
+        logic::{
+            printer::{is_printable, is_variants_printable},
+            Compiler,
+        },
+        output::{generate_finalize, generate_output, output_ty, struct_generics},
+    },
     transform::lookahead::inject_lookahead,
-    utils::resolve_crate_root,
+    utils::{proc_macro2_crate, resolve_crate_root},
 };
 
 impl Compiler {
@@ -44,6 +56,12 @@ impl Compiler {
                 (Quantity::Optional, MatcherKind::Nested(patterns)) => {
                     self.compile_anonymous_optional_nested(patterns, span)
                 }
+                (Quantity::Lookahead { negative }, MatcherKind::Nested(patterns)) => {
+                    self.compile_lookahead_nested(patterns, span, *negative)
+                }
+                (Quantity::One, MatcherKind::Unordered { separator, members }) => {
+                    self.compile_unordered(separator, members)
+                }
                 // 如果有 Anonymous + Many 或其他情况，可以在此补充
                 _ => self.compile_general_matcher(binder, quantity, matcher, span, &receiver),
             };
@@ -53,8 +71,17 @@ impl Compiler {
 
         // 3. 通用处理逻辑 (Named, Inline, 以及非 Nested 的 Anonymous)
         // 这些情况都可以归结为：解析一个具体的类型 T (Ty)
-        let t = self.compile_general_matcher(binder, quantity, matcher, span, &receiver);
-        tokens.extend(t);
+        let t = if let MatcherKind::Record { kind, children } = &matcher.kind {
+            self.compile_record(*kind, children, quantity, span, &receiver)
+        } else {
+            self.compile_general_matcher(binder, quantity, matcher, span, &receiver)
+        };
+
+        // 4. 校验子句
+        match &capture.validator {
+            Some(validator) => tokens.extend(generate_validation(binder, matcher, validator, t)),
+            None => tokens.extend(t),
+        }
         tokens
     }
 
@@ -67,57 +94,71 @@ impl Compiler {
         span: &Span,
         receiver: &TokenStream,
     ) -> TokenStream {
-        // A. 获取要解析的目标类型 (Type) 和对应的 parse trait
-        let (_ty, parse_trait) = match &matcher.kind {
-            MatcherKind::Enum { .. } | MatcherKind::SynType(_) => {
+        // A. 获取要解析的目标类型 (Type) 和对应的解析函数
+        let (_ty, parse_fn) = match &matcher.kind {
+            MatcherKind::Enum { .. } | MatcherKind::SynType(_) | MatcherKind::Named { .. } => {
                 let ty = self.compile_matcher(matcher);
-                (ty.clone(), quote! {<#ty as ::syn::parse::Parse>})
+                (ty.clone(), quote! {<#ty as ::syn::parse::Parse>::parse})
             }
+            MatcherKind::ParseWith { ty, parser } => (quote!(#ty), parse_fn(ty, Some(parser))),
             MatcherKind::Nested(patterns) => {
                 // 根据 Binder 类型生成结构体名称
-                let struct_ident = match binder {
-                    Binder::Named(name) => format_ident!("{}_Item", name),
-                    Binder::Inline(inline) => format_ident!("_{}", inline),
-                    _ => format_ident!("_Anon_Item"), // Fallback，通常不会走到这里
-                };
+                let struct_ident = nested_item_ident(binder);
                 let parse_trait_ident = format_ident!("_{}_Parse", struct_ident);
                 // 生成嵌套结构体并返回其类型
                 let ty = self.define_nested_parser(&struct_ident, patterns, *span);
                 // 使用完全限定语法调用自定义 trait，无需将 trait 导入当前作用域
                 let scope = crate::scope_context::get_scope_ident();
                 let qualified = if let Some(scope) = scope {
-                    quote! {<#ty as #scope::#parse_trait_ident>}
+                    quote! {<#ty as #scope::#parse_trait_ident>::parse}
                 } else {
-                    quote! {<#ty as #parse_trait_ident>}
+                    quote! {<#ty as #parse_trait_ident>::parse}
                 };
                 (ty, qualified)
             }
+            MatcherKind::Alternation(variants, index) => {
+                let item_ident = alternation_item_ident(binder, *index);
+                let is_index = matcher.kind.is_index_alternation();
+                let ty = self.define_alternation(&item_ident, variants, is_index);
+                if is_index {
+                    let parse_trait_ident = format_ident!("_{}_Parse", item_ident);
+                    let parse_trait = scoped_path(&parse_trait_ident);
+                    (ty.clone(), quote! {<#ty as #parse_trait>::parse})
+                } else {
+                    (ty.clone(), quote! {<#ty as ::syn::parse::Parse>::parse})
+                }
+            }
+            MatcherKind::Record { .. } => unreachable!("records are compiled by compile_record"),
+            MatcherKind::Unordered { .. } => {
+                unreachable!("unordered groups are compiled by compile_unordered")
+            }
         };
 
         // B. 根据数量 (Quantity) 生成解析动作
         match quantity {
             Quantity::One => {
                 quote! {
-                    #receiver #parse_trait::parse(&input)?;
+                    #receiver #parse_fn(&input)?;
                 }
             }
             Quantity::Optional => {
                 quote! {
                     {
                         let _fork = input.fork();
-                        if let ::std::result::Result::Ok(_parsed) = #parse_trait::parse(&_fork) {
+                        if let ::std::result::Result::Ok(_parsed) = #parse_fn(&_fork) {
                             #receiver ::std::option::Option::Some(_parsed);
                             ::syn::parse::discouraged::Speculative::advance_to(input, &_fork);
                         }
                     }
                 }
             }
-            Quantity::Many(separator) => {
-                quote! {
-                    {
-                        #receiver input.parse_terminated(#parse_trait::parse, #separator)?;
-                    }
-                }
+            Quantity::Bounded {
+                separator,
+                min,
+                max,
+            } => generate_repeat(&parse_fn, receiver, separator.as_ref(), *min, *max),
+            Quantity::Lookahead { negative } => {
+                generate_lookahead(quote! { #parse_fn(&_fork).map(|_| ()) }, *negative)
             }
         }
     }
@@ -143,6 +184,9 @@ impl Compiler {
 
         let (capture_init, struct_def, struct_expr, _) =
             generate_output(&captures, Some(item_name.clone()), None);
+        let finalize = generate_finalize(&captures);
+        let generics = struct_generics(&captures, true);
+        let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
 
         let pattern_tokens = self.compile_pattern(&patterns_group);
 
@@ -159,30 +203,218 @@ impl Compiler {
         let parse_trait = format_ident!("_{}_Parse", item_name);
         self.define_invisible_item(parse_quote! {
             #[allow(non_camel_case_types)]
-            pub trait #parse_trait {
-                fn parse(input: ::syn::parse::ParseStream) -> ::syn::Result<#item_name>;
+            pub trait #parse_trait: ::std::marker::Sized {
+                fn parse(input: ::syn::parse::ParseStream) -> ::syn::Result<Self>;
             }
         });
 
         // 3. 实现 Trait
         self.define_invisible_item(parse_quote! {
-            impl #parse_trait for #item_name {
+            impl #impl_generics #parse_trait for #item_name #ty_generics #where_clause {
                 fn parse(input: ::syn::parse::ParseStream) -> ::syn::Result<Self> {
                     #capture_init
                     #pattern_tokens
+                    #finalize
                     ::std::result::Result::Ok(#struct_expr)
                 }
             }
         });
 
-        // 3. 返回类型路径
+        // 4. 实现打印逻辑
+        if is_printable(&patterns_group) {
+            let printer_tokens = self.compile_printer(&patterns_group);
+            let printer_impl = Self::generate_printer_impl(
+                item_name,
+                &generics,
+                &captures,
+                &struct_expr,
+                &printer_tokens,
+            );
+            printer_impl
+                .into_iter()
+                .for_each(|item| self.define_invisible_item(item));
+        }
+
+        // 5. 返回类型路径
         if let Some(scope) = crate::scope_context::get_scope_ident() {
-            quote!(#scope::#item_name)
+            quote!(#scope::#item_name #ty_generics)
         } else {
-            quote!(#item_name)
+            quote!(#item_name #ty_generics)
         }
     }
 
+    /// 定义具名的嵌套结构体及其 `Parse` 实现，与外层结构体定义在同一作用域，返回其类型
+    fn define_named_item(
+        &mut self,
+        item: &NamedItem,
+        patterns: &[Pattern],
+        span: Span,
+    ) -> TokenStream {
+        let NamedItem {
+            attrs,
+            visibility,
+            ident,
+            ..
+        } = item;
+        let optimized_list = inject_lookahead(patterns.to_vec());
+        let patterns_group = Pattern {
+            kind: PatternKind::Group {
+                delimiter: Delimiter::None,
+                children: optimized_list,
+            },
+            span,
+            meta: None,
+        };
+
+        let captures = patterns_group.collect_captures();
+        let (capture_init, struct_def, struct_expr, _) =
+            generate_output(&captures, Some(ident.clone()), Some(visibility.clone()));
+        let finalize = generate_finalize(&captures);
+        let generics = struct_generics(&captures, true);
+        let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+        let pattern_tokens = self.compile_pattern(&patterns_group);
+
+        // 未指定 derive 时沿用外层的 derive，保证外层的 derive 仍然成立
+        let derive_attrs = if attrs.iter().any(|attr| attr.path().is_ident("derive")) {
+            vec![]
+        } else {
+            self.derive_attrs.clone()
+        };
+        self.shared_definition.push(parse_quote! {
+            #(#derive_attrs)*
+            #(#attrs)*
+            #struct_def
+        });
+        self.shared_definition.push(parse_quote! {
+            impl #impl_generics ::syn::parse::Parse for #ident #ty_generics #where_clause {
+                fn parse(input: ::syn::parse::ParseStream) -> ::syn::Result<Self> {
+                    #capture_init
+                    #pattern_tokens
+                    #finalize
+                    ::std::result::Result::Ok(#struct_expr)
+                }
+            }
+        });
+        if is_printable(&patterns_group) {
+            let printer_tokens = self.compile_printer(&patterns_group);
+            let printer_impl = Self::generate_printer_impl(
+                ident,
+                &generics,
+                &captures,
+                &struct_expr,
+                &printer_tokens,
+            );
+            self.shared_definition.extend(printer_impl);
+        }
+
+        quote!(#ident #ty_generics)
+    }
+
+    /// 定义匿名选择对应的类型，并返回该类型的路径
+    ///
+    /// 分支产生值时生成枚举 (`_0`、`_1`……) 并实现 `Parse`；
+    /// 否则生成标记结构体，通过私有的 `_<Name>_Parse` trait 解析出分支索引
+    fn define_alternation(
+        &mut self,
+        item_ident: &Ident,
+        variants: &[(EnumVariant, Matcher)],
+        is_index: bool,
+    ) -> TokenStream {
+        // 分支中的多态枚举需要先行定义
+        for (_, matcher) in variants {
+            if let MatcherKind::Enum { .. } = matcher.kind {
+                self.compile_matcher(matcher);
+            }
+        }
+        let enum_name: Type = parse_quote!(#item_ident);
+        let err_tokens = self.generate_error_token(variants);
+        let derive_attrs = self.derive_attrs.clone();
+
+        if is_index {
+            let patterns: Vec<_> = variants
+                .iter()
+                .filter_map(|(v, _)| match v {
+                    EnumVariant::Capture { pattern, .. } => Some(pattern.as_ref()),
+                    EnumVariant::Type { .. } => None,
+                })
+                .collect();
+            let branches = patterns
+                .iter()
+                .enumerate()
+                .map(|(i, pattern)| {
+                    let pattern_tokens = self.compile_pattern(pattern);
+                    Branch {
+                        first: First::of_pattern(pattern),
+                        prelude: quote! {
+                            let parser = |input: ::syn::parse::ParseStream| -> ::syn::Result<()> {
+                                #pattern_tokens
+                                ::std::result::Result::Ok(())
+                            };
+                        },
+                        callee: quote!(parser),
+                        ok: Some(quote!(#i)),
+                    }
+                })
+                .collect();
+            let parser = dispatch_branches(branches);
+            let parse_trait = format_ident!("_{}_Parse", item_ident);
+            self.define_invisible_item(parse_quote! {
+                #[allow(non_camel_case_types)]
+                pub struct #item_ident;
+            });
+            self.define_invisible_item(parse_quote! {
+                #[allow(non_camel_case_types)]
+                pub trait #parse_trait {
+                    fn parse(input: ::syn::parse::ParseStream) -> ::syn::Result<usize>;
+                }
+            });
+            self.define_invisible_item(parse_quote! {
+                impl #parse_trait for #item_ident {
+                    fn parse(input: ::syn::parse::ParseStream) -> ::syn::Result<usize> {
+                        #(#parser)*
+                        #err_tokens
+                    }
+                }
+            });
+            if patterns.iter().all(|p| is_printable(p)) {
+                let printer_impl = self.index_printer_impl(item_ident, &patterns);
+                printer_impl
+                    .into_iter()
+                    .for_each(|item| self.define_invisible_item(item));
+            }
+        } else {
+            let generics = variants_generics(variants);
+            let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+            let variants_struct = self.generate_variant_struct(variants);
+            self.define_invisible_item(parse_quote! {
+                #(#derive_attrs)*
+                #[allow(non_camel_case_types)]
+                pub enum #item_ident #generics #where_clause {
+                    #variants_struct
+                }
+            });
+            let parser = self.generate_parser(variants, &enum_name);
+            let pkg = resolve_crate_root();
+            self.define_invisible_item(parse_quote! {
+                impl #impl_generics ::syn::parse::Parse for #item_ident #ty_generics #where_clause {
+                    fn parse(input: ::syn::parse::ParseStream) -> ::syn::Result<Self> {
+                        #[allow(unused_imports)]
+                        use #pkg::__private::{HelpQuery, PriorityHigh, HelpImplDefault, HelpImplCustom};
+                        #(#parser)*
+                        #err_tokens
+                    }
+                }
+            });
+            if is_variants_printable(variants) {
+                let item = self.enum_printer_impl(variants, &enum_name, &generics);
+                self.define_invisible_item(item);
+            }
+            let path = scoped_path(item_ident);
+            return quote!(#path #ty_generics);
+        }
+        scoped_path(item_ident)
+    }
+
     /// 辅助函数：生成 Binder 对应的接收器代码
     fn compile_binder_receiver(&self, binder: &Binder) -> TokenStream {
         match binder {
@@ -215,24 +447,17 @@ impl Compiler {
         let captures = patterns.collect_captures();
         let (capture_init, struct_def, struct_expr, fields) =
             generate_output(&captures, None, None);
+        let output_ty = output_ty(&captures);
 
         let assigns_err = fields.iter().map(|ident| {
             quote! { #ident = ::std::option::Option::None; }
         });
 
-        let assigns_ok = captures.iter().enumerate().map(|(i, cap)| {
-            let ident = &fields[i];
-            let access = if cap.is_inline {
-                LitInt::new(&i.to_string(), Span::call_site()).into_token_stream()
-            } else {
-                quote! {#ident}
-            };
-            quote! { #ident = ::std::option::Option::Some(output.#access); }
-        });
+        let assigns_ok = optional_assigns(&captures, &fields);
 
         quote! {
             #struct_def
-            let _parser = |input: ::syn::parse::ParseStream| -> ::syn::Result<Output> {
+            let _parser = |input: ::syn::parse::ParseStream| -> ::syn::Result<#output_ty> {
                 #capture_init
                 #joint_token
                 ::std::result::Result::Ok(#struct_expr)
@@ -249,6 +474,280 @@ impl Compiler {
             }
         }
     }
+    /// 无序组：循环匹配各成员的键，成员的解析结果先暂存，循环结束后检查必需成员并写入字段
+    fn compile_unordered(&mut self, separator: &Separator, members: &[Member]) -> TokenStream {
+        let mut keyword_map = KeywordMap::new();
+        separator.keyword.define(&mut keyword_map);
+
+        let mut prelude = vec![];
+        let mut branches = vec![];
+        let mut finish = vec![];
+        for (i, member) in members.iter().enumerate() {
+            let key = &member.key;
+            key.define(&mut keyword_map);
+            let slot = format_ident!("_member_{}", i);
+            let parser = format_ident!("_parse_member_{}", i);
+
+            let inner = Pattern {
+                kind: PatternKind::Group {
+                    delimiter: Delimiter::None,
+                    children: inject_lookahead(member.inner.clone()),
+                },
+                span: member.pattern.span,
+                meta: None,
+            };
+            let inner_tokens = self.compile_pattern(&inner);
+            let inner_fields = inner.collect_captures();
+            let (capture_init, ..) = generate_output(&inner_fields, None, None);
+            let names = inner_fields.iter().map(|f| &f.name);
+            let tys: Vec<_> = inner_fields.iter().map(|f| &f.ty).collect();
+            prelude.push(quote! {
+                let mut #slot: ::std::option::Option<(#(#tys,)*)> = ::std::option::Option::None;
+                let #parser = |input: ::syn::parse::ParseStream| -> ::syn::Result<(#(#tys,)*)> {
+                    #capture_init
+                    #inner_tokens
+                    ::std::result::Result::Ok((#(#names,)*))
+                };
+            });
+
+            let message = format!("`{}` specified twice", key);
+            branches.push(quote! {
+                if input.peek(#key) {
+                    if #slot.is_some() {
+                        return ::std::result::Result::Err(::syn::Error::new(input.span(), #message));
+                    }
+                    #slot = ::std::option::Option::Some(#parser(input)?);
+                }
+            });
+
+            // 可选成员的字段在外层被包裹为 Option
+            let outer_fields = member.pattern.collect_captures();
+            let assigns =
+                outer_fields
+                    .iter()
+                    .zip(&inner_fields)
+                    .enumerate()
+                    .map(|(j, (outer, inner))| {
+                        let name = &outer.name;
+                        let index = syn::Index::from(j);
+                        if outer.is_optional && !inner.is_optional {
+                            quote! { #name = ::std::option::Option::Some(_v.#index); }
+                        } else {
+                            quote! { #name = _v.#index; }
+                        }
+                    });
+            finish.push(if member.optional {
+                quote! {
+                    if let ::std::option::Option::Some(_v) = #slot {
+                        #(#assigns)*
+                    }
+                }
+            } else {
+                let message = format!("missing `{}`", key);
+                quote! {
+                    let ::std::option::Option::Some(_v) = #slot else {
+                        return ::std::result::Result::Err(::syn::Error::new(_span, #message));
+                    };
+                    #(#assigns)*
+                }
+            });
+        }
+
+        let (on_miss, punct) = match separator.trailing {
+            Trailing::Optional => (
+                quote! {},
+                quote! {
+                    if !input.peek(#separator) {
+                        break;
+                    }
+                    input.parse::<#separator>()?;
+                },
+            ),
+            // 分隔符之后必须跟随成员
+            Trailing::Forbidden => {
+                let keys = members
+                    .iter()
+                    .map(|m| format!("`{}`", m.key))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                let message = format!("expected one of {}", keys);
+                (
+                    quote! {
+                        if _after_separator {
+                            return ::std::result::Result::Err(input.error(#message));
+                        }
+                    },
+                    quote! {
+                        if !input.peek(#separator) {
+                            break;
+                        }
+                        input.parse::<#separator>()?;
+                        _after_separator = true;
+                    },
+                )
+            }
+            // 每个成员之后必须跟随分隔符
+            Trailing::Required => (
+                quote! {},
+                quote! {
+                    input.parse::<#separator>()?;
+                },
+            ),
+        };
+        let after_separator = matches!(separator.trailing, Trailing::Forbidden)
+            .then(|| quote! { let mut _after_separator = false; });
+
+        let keyword_map_tokens = self.compile_keyword_map(keyword_map);
+        // 键的定义放在内层，避免与同名字段冲突
+        quote! {
+            {
+                let _span = input.span();
+                #(#prelude)*
+                {
+                    #keyword_map_tokens
+                    #after_separator
+                    loop {
+                        #(#branches else)* {
+                            #on_miss
+                            break;
+                        }
+                        #punct
+                    }
+                }
+                #(#finish)*
+            }
+        }
+    }
+
+    /// 记录子模式：子模式中的捕获展开到当前层级，并将覆盖的 span、原始 token 或分隔符写入接收器
+    fn compile_record(
+        &mut self,
+        kind: RecordKind,
+        patterns: &[Pattern],
+        quantity: &Quantity,
+        span: &Span,
+        receiver: &TokenStream,
+    ) -> TokenStream {
+        let optimized_list = inject_lookahead(patterns.to_vec());
+        let patterns = Pattern {
+            kind: PatternKind::Group {
+                delimiter: Delimiter::None,
+                children: optimized_list,
+            },
+            span: *span,
+            meta: None,
+        };
+        let pattern_tokens = self.compile_pattern(&patterns);
+
+        // 解析子模式，并将记录的值绑定到 `_record`
+        let record = match kind {
+            RecordKind::Span | RecordKind::Tokens => {
+                let consumed = consumed_tokens();
+                // `Span::join` 仅在 nightly 上可用，stable 上退化为首个 token 的 span
+                let value = if let RecordKind::Span = kind {
+                    quote! {
+                        {
+                            let mut _iter = _consumed.into_iter();
+                            match _iter.next() {
+                                ::std::option::Option::Some(_first) => {
+                                    let _first = _first.span();
+                                    let _last = _iter.last().map(|_tt| _tt.span()).unwrap_or(_first);
+                                    _first.join(_last).unwrap_or(_first)
+                                }
+                                ::std::option::Option::None => input.span(),
+                            }
+                        }
+                    }
+                } else {
+                    quote! { _consumed }
+                };
+                quote! {
+                    let _begin = input.cursor();
+                    #pattern_tokens
+                    #consumed
+                    let _record = #value;
+                }
+            }
+            RecordKind::Delimiter(delimiter) => {
+                let mac = match delimiter {
+                    Delimiter::Brace => quote! { ::syn::braced! },
+                    Delimiter::Bracket => quote! { ::syn::bracketed! },
+                    _ => quote! { ::syn::parenthesized! },
+                };
+                quote! {
+                    let _content;
+                    let _record = #mac(_content in input);
+                    {
+                        let input = &_content;
+                        #pattern_tokens
+                        if !input.is_empty() {
+                            return ::std::result::Result::Err(input.error("unexpected token"));
+                        }
+                    }
+                }
+            }
+        };
+
+        match quantity {
+            Quantity::Optional => {
+                let ty = kind.ty();
+                let captures = patterns.collect_captures();
+                let (capture_init, struct_def, struct_expr, fields) =
+                    generate_output(&captures, None, None);
+                let output_ty = output_ty(&captures);
+                let assigns_ok = optional_assigns(&captures, &fields);
+                quote! {
+                    {
+                        #struct_def
+                        let _parser = |input: ::syn::parse::ParseStream| -> ::syn::Result<(#output_ty, #ty)> {
+                            #capture_init
+                            #record
+                            ::std::result::Result::Ok((#struct_expr, _record))
+                        };
+                        let _fork = input.fork();
+                        if let ::std::result::Result::Ok((output, _record)) = _parser(&_fork) {
+                            ::syn::parse::discouraged::Speculative::advance_to(input, &_fork);
+                            #receiver ::std::option::Option::Some(_record);
+                            #(#assigns_ok)*
+                        }
+                    }
+                }
+            }
+            _ => quote! {
+                {
+                    #record
+                    #receiver _record;
+                }
+            },
+        }
+    }
+    /// 对嵌套模式的前瞻断言：在 fork 上运行模式，不消费输入
+    fn compile_lookahead_nested(
+        &mut self,
+        patterns: &[Pattern],
+        span: &Span,
+        negative: bool,
+    ) -> TokenStream {
+        let optimized_list = inject_lookahead(patterns.to_vec());
+        let patterns = Pattern {
+            kind: PatternKind::Group {
+                delimiter: Delimiter::None,
+                children: optimized_list,
+            },
+            span: *span,
+            meta: None,
+        };
+        let pattern_tokens = self.compile_pattern(&patterns);
+        generate_lookahead(
+            quote! {
+                (|input: ::syn::parse::ParseStream| -> ::syn::Result<()> {
+                    #pattern_tokens
+                    ::std::result::Result::Ok(())
+                })(&_fork)
+            },
+            negative,
+        )
+    }
     fn compile_matcher(&mut self, matcher: &Matcher) -> TokenStream {
         match &matcher.kind {
             MatcherKind::Enum {
@@ -257,13 +756,24 @@ impl Compiler {
             } => {
                 self.define_enum(enum_name, variants);
                 self.define_enum_parse_impl(variants, enum_name);
+                if is_variants_printable(variants) {
+                    self.define_enum_printer_impl(variants, enum_name);
+                }
                 quote!(#enum_name)
             }
-            MatcherKind::SynType(ty) => quote!(#ty),
-            MatcherKind::Nested(_) => quote! {},
+            MatcherKind::Named { item, children } => {
+                self.define_named_item(item, children, matcher.span)
+            }
+            MatcherKind::SynType(ty) | MatcherKind::ParseWith { ty, .. } => quote!(#ty),
+            MatcherKind::Nested(_)
+            | MatcherKind::Alternation(..)
+            | MatcherKind::Record { .. }
+            | MatcherKind::Unordered { .. } => {
+                quote! {}
+            }
         }
     }
-    fn generate_variant_struct(
+    pub fn generate_variant_struct(
         &self,
         variants: &[(EnumVariant, Matcher)],
     ) -> Punctuated<TokenStream, Token![,]> {
@@ -277,7 +787,11 @@ impl Compiler {
                     let body = if named {
                         let fields: Punctuated<_, Comma> = fields
                             .iter()
-                            .map(|FieldDef { name, ty, .. }| quote! {#name: #ty})
+                            .map(|field| {
+                                let (name, ty, attrs) =
+                                    (&field.name, field.public_ty(), &field.attrs);
+                                quote! {#(#attrs)* #name: #ty}
+                            })
                             .collect();
                         quote! {
                             {
@@ -288,14 +802,14 @@ impl Compiler {
                         quote! {}
                     } else {
                         let fields: Punctuated<_, Comma> =
-                            fields.iter().map(|FieldDef { ty, .. }| ty).collect();
+                            fields.iter().map(FieldDef::public_ty).collect();
                         quote! {
                             (#fields)
                         }
                     };
                     quote! { #ident #body }
                 }
-                EnumVariant::Type { ident, ty } => {
+                EnumVariant::Type { ident, ty, .. } => {
                     quote! {#ident(#ty)}
                 }
             })
@@ -316,48 +830,53 @@ impl Compiler {
         variants: &[(EnumVariant, Matcher)],
         enum_name: &Type,
     ) -> Vec<TokenStream> {
-        variants.iter().map(|(v, ..)| match v {
-            EnumVariant::Type { ident, ty } => {
-                quote! {
-                    let _fork = input.fork();
-                    if let ::std::result::Result::Ok(v) = <#ty as ::syn::parse::Parse>::parse(&_fork) {
-                        ::syn::parse::discouraged::Speculative::advance_to(input, &_fork);
-                        return ::std::result::Result::Ok(#enum_name::#ident(v));
-                    };
-                }
-            }
-            EnumVariant::Capture {
-                ident,
-                pattern,
-                ..
-            } => {
-                // 在 codegen 阶段重新收集 captures，确保 scope 已设置
-                let fields = pattern.collect_captures();
-                let named = fields.first().map(|f| !f.is_inline).unwrap_or(false);
-                let (capture_init, _, _, capture_list) = generate_output(&fields, None, None);
-                let pattern_tokens = self.compile_pattern(pattern);
-                let enum_expr_body = capture_list.iter().collect::<Punctuated<_, Token![,]>>();
-                let enum_expr = if named {
-                    quote! {{#enum_expr_body}}
-                } else if fields.is_empty() {
-                    quote! {}
-                } else {
-                    quote! {(#enum_expr_body)}
-                };
-                quote! {
-                    let _fork = input.fork();
-                    let parser = |input: ::syn::parse::ParseStream| -> ::syn::Result<#enum_name> {
-                        #capture_init
-                        #pattern_tokens
-                        return ::std::result::Result::Ok(#enum_name::#ident #enum_expr);
-                    };
-                    if let ::std::result::Result::Ok(v) = parser(&_fork) {
-                        ::syn::parse::discouraged::Speculative::advance_to(input, &_fork);
-                        return ::std::result::Result::Ok(v);
+        let branches = variants
+            .iter()
+            .map(|(v, ..)| match v {
+                EnumVariant::Type { ident, ty, parser } => Branch {
+                    first: First::of_variant(v),
+                    prelude: quote! {},
+                    callee: parse_fn(ty, parser.as_deref()),
+                    ok: Some(quote!(#enum_name::#ident(_v))),
+                },
+                EnumVariant::Capture { ident, pattern, .. } => {
+                    // 在 codegen 阶段重新收集 captures，确保 scope 已设置
+                    let fields = pattern.collect_captures();
+                    let named = fields.first().map(|f| !f.is_inline).unwrap_or(false);
+                    let (capture_init, ..) = generate_output(&fields, None, None);
+                    let finalize = generate_finalize(&fields);
+                    let pattern_tokens = self.compile_pattern(pattern);
+                    let enum_expr_body = fields
+                        .iter()
+                        .map(|field| {
+                            let (name, cfg) = (&field.name, field.cfg_attrs());
+                            quote! { #(#cfg)* #name }
+                        })
+                        .collect::<Punctuated<_, Token![,]>>();
+                    let enum_expr = if named {
+                        quote! {{#enum_expr_body}}
+                    } else if fields.is_empty() {
+                        quote! {}
+                    } else {
+                        quote! {(#enum_expr_body)}
                     };
+                    Branch {
+                        first: First::of_variant(v),
+                        prelude: quote! {
+                            let parser = |input: ::syn::parse::ParseStream| -> ::syn::Result<Self> {
+                                #capture_init
+                                #pattern_tokens
+                                #finalize
+                                return ::std::result::Result::Ok(#enum_name::#ident #enum_expr);
+                            };
+                        },
+                        callee: quote!(parser),
+                        ok: None,
+                    }
                 }
-            }
-        }).collect()
+            })
+            .collect();
+        dispatch_branches(branches)
     }
     fn generate_error_token(&self, variants: &[(EnumVariant, Matcher)]) -> TokenStream {
         let pkg = resolve_crate_root();
@@ -366,19 +885,25 @@ impl Compiler {
 
         variants.iter().for_each(|(v, _)| match v {
             EnumVariant::Type { ty, .. } => {
-                fmt_str.push("{}");
+                fmt_str.push(String::from("{}"));
                 fmt_args.push(parse_quote!(#pkg::__private::HelpQuery::<#ty>::new().get_message(&PriorityHigh)))
             }
-            EnumVariant::Capture { .. } => fmt_str.push("pattern(not impl)"),
+            EnumVariant::Capture { pattern, .. } => {
+                let example = describe_pattern(pattern);
+                fmt_str.push(format!("`{}`", example.replace('{', "{{").replace('}', "}}")))
+            }
         });
         let fmt_str = fmt_str.join(", ").to_string();
-        //
+        // 有分支前进过时，报告前进最远的分支自身的错误
         quote! {
+            if let ::std::option::Option::Some((_, _err)) = _furthest {
+                return ::std::result::Result::Err(_err);
+            }
             ::std::result::Result::Err(
                 ::syn::Error::new(
                     input.span(),
                     format!(
-                        stringify!(Expected one of: {}, get: {}),
+                        "Expected one of: {}, get: {}",
                         format!(#fmt_str, #fmt_args),
                         input
                     )
@@ -386,7 +911,11 @@ impl Compiler {
             )
         }
     }
-    fn define_enum_parse_impl(&mut self, variants: &[(EnumVariant, Matcher)], enum_name: &Type) {
+    pub fn define_enum_parse_impl(
+        &mut self,
+        variants: &[(EnumVariant, Matcher)],
+        enum_name: &Type,
+    ) {
         let parser = self.generate_parser(variants, enum_name);
         let err_tokens = self.generate_error_token(variants);
         let pkg = resolve_crate_root();
@@ -403,3 +932,429 @@ impl Compiler {
         self.shared_definition.push(parse_impl);
     }
 }
+
+/// 根据 Binder 生成嵌套结构体的名称
+pub fn nested_item_ident(binder: &Binder) -> Ident {
+    match binder {
+        Binder::Named(name) => format_ident!("{}_Item", name),
+        Binder::Inline(inline) => format_ident!("_{}", inline),
+        _ => format_ident!("_Anon_Item"), // Fallback，通常不会走到这里
+    }
+}
+
+/// 生成解析 `ty` 的函数：默认为 `<ty as Parse>::parse`，
+/// 指定了 `with` 时将自定义函数转换为函数指针，以便在类型不符时给出明确的错误
+fn parse_fn(ty: &Type, parser: Option<&Expr>) -> TokenStream {
+    match parser {
+        Some(parser) => quote! {
+            (#parser as fn(::syn::parse::ParseStream) -> ::syn::Result<#ty>)
+        },
+        None => quote! { <#ty as ::syn::parse::Parse>::parse },
+    }
+}
+
+/// 多态枚举或选择的一个分支
+struct Branch {
+    /// 分支可能的开头
+    first: First,
+    /// 解析函数的定义 (e.g. `let parser = |input| { ... };`)
+    prelude: TokenStream,
+    /// 解析函数，接收 `ParseStream`
+    callee: TokenStream,
+    /// 成功时的返回值，解析结果为 `_v`；`None` 即直接返回解析结果
+    ok: Option<TokenStream>,
+}
+
+/// 按 FIRST 集合分派各分支：
+/// 开头可确定的分支先 `peek`，不满足时直接跳过；
+/// 若之后没有开头可能相同的分支，`peek` 成功后直接在 `input` 上解析，不再 fork；
+/// 其余分支仍在 fork 上逐个尝试
+fn dispatch_branches(branches: Vec<Branch>) -> Vec<TokenStream> {
+    let mut keywords = KeywordMap::new();
+    let peeks: Vec<_> = branches
+        .iter()
+        .map(|branch| branch.first.peek(&mut keywords))
+        .collect();
+
+    let mut tokens = vec![furthest_init()];
+    if peeks.iter().any(Option::is_some) {
+        let definitions = keywords.0.values();
+        let peeks = peeks
+            .iter()
+            .map(|peek| peek.clone().unwrap_or(quote!(true)));
+        // 关键字定义放在独立的块中，避免与同名的字段冲突
+        tokens.push(quote! {
+            let _peek = {
+                #(#definitions)*
+                [#(#peeks),*]
+            };
+        });
+    }
+    for (i, branch) in branches.iter().enumerate() {
+        let Branch {
+            first,
+            prelude,
+            callee,
+            ok,
+        } = branch;
+        let exclusive =
+            peeks[i].is_some() && branches[i + 1..].iter().all(|b| !b.first.overlaps(first));
+        let body = if exclusive {
+            let result = match ok {
+                Some(ok) => quote!(#callee(input).map(|_v| #ok)),
+                None => quote!(#callee(input)),
+            };
+            quote! {
+                #prelude
+                return #result;
+            }
+        } else {
+            let ok = ok.clone().unwrap_or(quote!(_v));
+            let branch = speculative_branch(callee, &ok);
+            quote! {
+                #prelude
+                #branch
+            }
+        };
+        tokens.push(match peeks[i] {
+            Some(_) => quote! {
+                if _peek[#i] {
+                    #body
+                }
+            },
+            None => body,
+        });
+    }
+    tokens
+}
+
+/// 记录前进最远的失败分支：`(fork 停下的位置, 错误)`
+fn furthest_init() -> TokenStream {
+    quote! {
+        #[allow(unused_mut)]
+        let mut _furthest: ::std::option::Option<(::syn::buffer::Cursor, ::syn::Error)> =
+            ::std::option::Option::None;
+    }
+}
+
+/// 在 fork 上尝试一个分支，成功则返回 `ok`（解析结果为 `_v`）；
+/// 失败且比之前的分支前进得更远时记录其错误
+fn speculative_branch(callee: &TokenStream, ok: &TokenStream) -> TokenStream {
+    quote! {
+        let _fork = input.fork();
+        match #callee(&_fork) {
+            ::std::result::Result::Ok(_v) => {
+                ::syn::parse::discouraged::Speculative::advance_to(input, &_fork);
+                return ::std::result::Result::Ok(#ok);
+            }
+            ::std::result::Result::Err(_err) => {
+                let _start = _furthest.as_ref().map_or(input.cursor(), |(c, _)| *c);
+                if _fork.cursor() > _start {
+                    _furthest = ::std::option::Option::Some((_fork.cursor(), _err));
+                }
+            }
+        }
+    }
+}
+
+/// 为隐藏作用域中的条目生成路径
+fn scoped_path(ident: &Ident) -> TokenStream {
+    if let Some(scope) = crate::scope_context::get_scope_ident() {
+        quote!(#scope::#ident)
+    } else {
+        quote!(#ident)
+    }
+}
+
+/// `Optional` 解析成功后，将 `output` 中的捕获以 `Some` 写回当前层级
+fn optional_assigns(captures: &[FieldDef], fields: &[Ident]) -> Vec<TokenStream> {
+    captures
+        .iter()
+        .enumerate()
+        .map(|(i, cap)| {
+            let ident = &fields[i];
+            let access = if cap.is_inline {
+                LitInt::new(&i.to_string(), Span::call_site()).into_token_stream()
+            } else {
+                quote! {#ident}
+            };
+            quote! { #ident = ::std::option::Option::Some(output.#access); }
+        })
+        .collect()
+}
+
+/// 生成解析字段后运行校验子句的代码
+///
+/// 校验失败时报错覆盖该捕获消费的区域；若字段类型通过 `help!` 提供了示例，则附加在错误信息后
+fn generate_validation(
+    binder: &Binder,
+    matcher: &Matcher,
+    validator: &Expr,
+    parse_tokens: TokenStream,
+) -> TokenStream {
+    let pkg = resolve_crate_root();
+    let field = match binder {
+        Binder::Named(ident) => ident.clone(),
+        Binder::Inline(i) => format_ident!("_{}", i),
+        Binder::Anonymous => return parse_tokens,
+    };
+    let example = match &matcher.kind {
+        MatcherKind::SynType(ty)
+        | MatcherKind::ParseWith { ty, .. }
+        | MatcherKind::Enum { enum_name: ty, .. } => quote! {
+            {
+                #[allow(unused_imports)]
+                use #pkg::__private::{ExampleImplCustom, ExampleImplDefault, ExampleQuery};
+                (&ExampleQuery::<#ty>::new()).get_example()
+            }
+        },
+        _ => quote! { ::std::option::Option::<::std::string::String>::None },
+    };
+    let consumed = consumed_tokens();
+    quote! {
+        let _begin = input.cursor();
+        #parse_tokens
+        // 校验子句只接受闭包，`|v| f(v)` 不应触发 clippy::redundant_closure
+        #[allow(clippy::redundant_closure)]
+        let _validated = #pkg::__private::validate(&#field, #validator);
+        if let ::std::result::Result::Err(_err) = _validated {
+            // 校验函数返回 syn::Error 时原样返回，其余错误转换为指向该捕获的错误信息
+            #[allow(unused_imports)]
+            use #pkg::__private::{ValidationError, ValidationImplDefault, ValidationImplSyn};
+            let _message = (&ValidationError(_err)).message()?;
+            let _message = match #example {
+                ::std::option::Option::Some(_example) => {
+                    ::std::format!("{}\nexample: {}", _message, _example)
+                }
+                ::std::option::Option::None => _message,
+            };
+            #consumed
+            return ::std::result::Result::Err(if _consumed.is_empty() {
+                ::syn::Error::new(input.span(), _message)
+            } else {
+                ::syn::Error::new_spanned(_consumed, _message)
+            });
+        }
+    }
+}
+
+/// 收集 `_begin` 至当前位置之间已消费的 token，绑定到 `_consumed`
+fn consumed_tokens() -> TokenStream {
+    let proc_macro2 = proc_macro2_crate();
+    quote! {
+        let mut _consumed = #proc_macro2::TokenStream::new();
+        let mut _cursor = _begin;
+        while _cursor != input.cursor() {
+            let ::std::option::Option::Some((_tt, _next)) = _cursor.token_tree() else {
+                break;
+            };
+            _consumed.extend(::std::iter::once(_tt));
+            _cursor = _next;
+        }
+    }
+}
+
+/// 生成前瞻断言的代码：`check` 在 `_fork` 上求值为 `syn::Result<()>`，输入本身不会前进
+///
+/// 正向断言失败时返回 `check` 的错误；负向断言在 `check` 成功时报错指向当前 token。
+fn generate_lookahead(check: TokenStream, negative: bool) -> TokenStream {
+    let assertion = if negative {
+        quote! {
+            if _matched.is_ok() {
+                let _found = input
+                    .cursor()
+                    .token_tree()
+                    .map(|(_tt, _)| _tt.to_string())
+                    .unwrap_or_default();
+                return ::std::result::Result::Err(
+                    input.error(::std::format!("unexpected `{}`", _found)),
+                );
+            }
+        }
+    } else {
+        quote! { _matched?; }
+    };
+    quote! {
+        {
+            let _fork = input.fork();
+            let _matched: ::syn::Result<()> = #check;
+            #assertion
+        }
+    }
+}
+
+/// 生成重复解析的代码
+///
+/// - 无分隔符：重复解析直到流为空，或元素在未消费任何 token 的情况下解析失败，结果为 `Vec<T>`
+/// - 默认分隔符 `[sep]`：与 `parse_terminated` 相同，消费剩余的整个流，结果为 `Punctuated<T, sep>`
+/// - `[sep!]` / `[sep+]`：元素后没有分隔符（或未消费任何 token）时停止，
+///   因此重复可以位于模式中间。末尾分隔符按 [`Trailing`] 处理
+///
+/// 后续字面量的边界由 edge 处理（已将输入截断至边界）。
+/// 超出 `max` 时报错指向多出的元素，不足 `min` 时报错覆盖已解析的区域。
+fn generate_repeat(
+    parse_fn: &TokenStream,
+    receiver: &TokenStream,
+    separator: Option<&Separator>,
+    min: usize,
+    max: Option<usize>,
+) -> TokenStream {
+    if let Some(
+        separator @ Separator {
+            trailing: Trailing::Optional,
+            ..
+        },
+    ) = separator
+    {
+        if min == 0 && max.is_none() {
+            return quote! {
+                {
+                    #receiver input.parse_terminated(#parse_fn, #separator)?;
+                }
+            };
+        }
+    }
+
+    let max_check = max.map(|max| {
+        let message = if min == max {
+            format!("expected exactly {}, found more", plural(max))
+        } else {
+            format!("expected at most {}", plural(max))
+        };
+        quote! {
+            if _items.len() >= #max {
+                return ::std::result::Result::Err(::syn::Error::new(input.span(), #message));
+            }
+        }
+    });
+    let consumed = consumed_tokens();
+    let min_check = (min > 0).then(|| {
+        let message = if Some(min) == max {
+            format!("expected exactly {}, found {{}}", plural(min))
+        } else {
+            format!("expected at least {}, found {{}}", plural(min))
+        };
+        quote! {
+            if _items.len() < #min {
+                let _message = ::std::format!(#message, _items.len());
+                #consumed
+                return ::std::result::Result::Err(if _consumed.is_empty() {
+                    ::syn::Error::new(input.span(), _message)
+                } else {
+                    ::syn::Error::new_spanned(_consumed, _message)
+                });
+            }
+        }
+    });
+    let begin = min_check
+        .as_ref()
+        .map(|_| quote! { let _begin = input.cursor(); });
+    let assign = if receiver.is_empty() {
+        quote! { let _ = _items; }
+    } else {
+        quote! { #receiver _items; }
+    };
+
+    // 尝试解析一个元素：流为空或未消费任何 token 即失败时结束重复
+    let speculative = |push: TokenStream| {
+        quote! {
+            if input.is_empty() {
+                break;
+            }
+            let _fork = input.fork();
+            match #parse_fn(&_fork) {
+                ::std::result::Result::Ok(_parsed) => {
+                    if _fork.cursor() == input.cursor() {
+                        break;
+                    }
+                    #max_check
+                    ::syn::parse::discouraged::Speculative::advance_to(input, &_fork);
+                    #push
+                }
+                ::std::result::Result::Err(err) => {
+                    if _fork.cursor() == input.cursor() {
+                        break;
+                    }
+                    return ::std::result::Result::Err(err);
+                }
+            }
+        }
+    };
+
+    let body = if let Some(separator) = separator {
+        let element = speculative(quote! { _items.push_value(_parsed); });
+        let (element, punct) = match separator.trailing {
+            // 消费剩余的整个流
+            Trailing::Optional => (
+                quote! {
+                    if input.is_empty() {
+                        break;
+                    }
+                    #max_check
+                    _items.push_value(#parse_fn(input)?);
+                },
+                quote! {
+                    if input.is_empty() {
+                        break;
+                    }
+                    _items.push_punct(input.parse::<#separator>()?);
+                },
+            ),
+            // 分隔符之后必须跟随元素
+            Trailing::Forbidden => (
+                quote! {
+                    if _items.is_empty() {
+                        #element
+                    } else {
+                        #max_check
+                        _items.push_value(#parse_fn(input)?);
+                    }
+                },
+                quote! {
+                    if !input.peek(#separator) {
+                        break;
+                    }
+                    _items.push_punct(input.parse::<#separator>()?);
+                },
+            ),
+            // 每个元素之后必须跟随分隔符
+            Trailing::Required => (
+                element,
+                quote! {
+                    _items.push_punct(input.parse::<#separator>()?);
+                },
+            ),
+        };
+        quote! {
+            let mut _items = ::syn::punctuated::Punctuated::new();
+            loop {
+                #element
+                #punct
+            }
+        }
+    } else {
+        let element = speculative(quote! { _items.push(_parsed); });
+        quote! {
+            let mut _items = ::std::vec::Vec::new();
+            loop {
+                #element
+            }
+        }
+    };
+    quote! {
+        {
+            #begin
+            #body
+            #min_check
+            #assign
+        }
+    }
+}
+
+fn plural(n: usize) -> String {
+    if n == 1 {
+        String::from("1 element")
+    } else {
+        format!("{n} elements")
+    }
+}
@@ -1,24 +1,42 @@
-use proc_macro2::TokenStream;
-use quote::quote;
-use syn::Local;
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::{parse_quote, Expr, Generics, Ident, Local, Type};
+use vacro_grammar::Node;
 
 use crate::{
-    ast::input::{BindInput, DefineInput},
+    ast::{
+        capture::{FieldDef, MatcherKind},
+        input::{BindInput, DefineInput, MatchArm, MatchInput},
+        node::{Pattern, PatternKind},
+    },
     codegen::{
-        logic::Compiler,
-        output::{generate_example, generate_output},
+        grammar::{build_grammar, generate_grammar_fn, generate_railroad_doc},
+        logic::{
+            printer::{is_printable, is_variants_printable},
+            Compiler,
+        },
+        output::{
+            define_generics, generate_define_output, generate_example, generate_finalize,
+            generate_output,
+        },
     },
     scope_context,
+    transform::{
+        lint::{lint, Lint},
+        recursion::resolve_recursion,
+    },
 };
 
 /// 入口部分
 impl Compiler {
     pub fn compile_capture_input(&mut self, input: &BindInput) -> TokenStream {
         scope_context::set_scope_ident(None);
+        scope_context::set_generics(None);
         let mut tokens = TokenStream::new();
 
         let BindInput {
             input,
+            fat_arrow,
             patterns,
             local: Local { let_token, pat, .. },
             suffix,
@@ -27,45 +45,158 @@ impl Compiler {
 
         self.target = Self::pat_to_ident(pat);
 
+        let warnings = lint(patterns)
+            .iter()
+            .map(Lint::to_warning)
+            .collect::<Vec<_>>();
+        let (parser, ..) = self.compile_bind_parser(patterns);
+
+        let parse = if fat_arrow.is_some() {
+            // 直接在已有的 ParseStream 上解析，不要求消费完毕
+            quote!(parser(#input))
+        } else {
+            let input = match input {
+                Expr::Path(_) | Expr::Field(_) | Expr::MethodCall(_) | Expr::Call(_) => {
+                    quote!(#input)
+                }
+                input => quote!((#input)),
+            };
+            quote!(::syn::parse::Parser::parse2(parser, #input.into()))
+        };
+
+        let shared_definition = &self.shared_definition;
+        tokens.extend(quote! {
+            #(#shared_definition)*
+            #(#warnings)*
+            #let_token #pat = {
+                #parser
+                #parse
+            }#suffix
+        });
+        tokens
+    }
+
+    pub fn compile_match_input(input: &MatchInput) -> TokenStream {
+        scope_context::set_scope_ident(None);
+        scope_context::set_generics(None);
+        let MatchInput { input, arms, .. } = input;
+
+        // 生成的局部变量不应与分支中用户的变量冲突
+        let source = Ident::new("input", Span::mixed_site());
+        let errors = Ident::new("errors", Span::mixed_site());
+        let error = Ident::new("error", Span::mixed_site());
+
+        let mut shared_definition = vec![];
+        let mut warnings = vec![];
+        // 从最后一个分支开始向前嵌套：前一个分支失败时才尝试下一个
+        let mut tokens = quote! {
+            ::std::result::Result::Err(#errors.expect("`match_tokens!` has at least one arm"))
+        };
+        for (index, MatchArm { patterns, body, .. }) in arms.iter().enumerate().rev() {
+            let Some(patterns) = patterns else {
+                tokens = quote!(::std::result::Result::Ok(#body));
+                continue;
+            };
+            let mut compiler = Compiler::new();
+            compiler.target = format_ident!("match_arm_{}", index);
+            warnings.extend(lint(patterns).iter().map(Lint::to_warning));
+            let (parser, captures, struct_expr) = compiler.compile_bind_parser(patterns);
+            shared_definition.append(&mut compiler.shared_definition);
+
+            let names = captures.iter().map(|field| &field.name).collect::<Vec<_>>();
+            tokens = quote! {
+                match {
+                    #parser
+                    ::syn::parse::Parser::parse2(
+                        parser,
+                        ::std::clone::Clone::clone(&#source).into(),
+                    )
+                    .map(|#struct_expr| (#(#names,)*))
+                } {
+                    ::std::result::Result::Ok((#(#names,)*)) => ::std::result::Result::Ok(#body),
+                    ::std::result::Result::Err(#error) => {
+                        match &mut #errors {
+                            ::std::option::Option::Some(combined) => combined.combine(#error),
+                            ::std::option::Option::None => #errors = ::std::option::Option::Some(#error),
+                        }
+                        #tokens
+                    }
+                }
+            };
+        }
+
+        quote! {
+            {
+                #(#shared_definition)*
+                #(#warnings)*
+                let #source = #input;
+                #[allow(unused_mut, unused_variables)]
+                let mut #errors: ::std::option::Option<::syn::Error> = ::std::option::Option::None;
+                #tokens
+            }
+        }
+    }
+
+    /// `bind!` 与 `match_tokens!` 共用：定义 `Output` 及解析闭包 `parser`
+    fn compile_bind_parser(
+        &mut self,
+        patterns: &Pattern,
+    ) -> (TokenStream, Vec<FieldDef>, TokenStream) {
         let patterns_tokens = self.compile_pattern(patterns);
         let captures = patterns.collect_captures();
         let example_items = patterns.collect_example();
 
-        let Compiler {
-            shared_definition,
-            scoped_definition,
-            ..
-        } = &self;
+        let (capture_init, struct_def, struct_expr, _) =
+            generate_output(&captures, Some(format_ident!("Output")), None);
+        let finalize = generate_finalize(&captures);
 
-        let (capture_init, struct_def, struct_expr, _) = generate_output(&captures, None, None);
+        // 元组形式的 Output 无法实现 ToTokens，直接使用其中的元素即可
+        let is_inline = captures.first().map(|f| f.is_inline).unwrap_or(false);
+        let printer_impl = if !is_inline && is_printable(patterns) {
+            let printer_tokens = self.compile_printer(patterns);
+            Self::generate_printer_impl(
+                &format_ident!("Output"),
+                &Generics::default(),
+                &captures,
+                &struct_expr,
+                &printer_tokens,
+            )
+        } else {
+            vec![]
+        };
+
+        let scoped_definition = &self.scoped_definition;
         let (example_doc, extra) = generate_example(&example_items, false, false, false);
         let extra = extra.iter().map(|e| {
             quote! {
                 #[doc = #e]
             }
         });
-        tokens.extend(quote! {
-            #(#shared_definition)*
-            #let_token #pat = {
-                #(#scoped_definition)*
-                use ::syn::parse::Parse;
-                #[doc = #example_doc]
-                #(#extra)*
-                #struct_def
-                let parser = |input: ::syn::parse::ParseStream| -> ::syn::Result<Output> {
-                    #capture_init
-                    #patterns_tokens
-                    ::std::result::Result::Ok(#struct_expr)
-                };
-                ::syn::parse::Parser::parse2(parser, #input.into())
-            }#suffix
-        });
-        tokens
+        let tokens = quote! {
+            #(#scoped_definition)*
+            use ::syn::parse::Parse;
+            #[doc = #example_doc]
+            #(#extra)*
+            #struct_def
+            #(#printer_impl)*
+            let parser = |input: ::syn::parse::ParseStream| -> ::syn::Result<Output> {
+                #capture_init
+                #patterns_tokens
+                #finalize
+                ::std::result::Result::Ok(#struct_expr)
+            };
+        };
+        (tokens, captures, struct_expr)
     }
+
     pub fn compile_define_input(&mut self, input: &DefineInput) -> TokenStream {
+        if input.enum_token.is_some() {
+            return self.compile_define_enum(input);
+        }
         let mut tokens = TokenStream::new();
         let DefineInput {
             name,
+            generics,
             patterns,
             visibility,
             attrs,
@@ -79,11 +210,39 @@ impl Compiler {
             .cloned()
             .collect();
         scope_context::set_scope_ident(Some(self.get_private_scope_ident()));
+        scope_context::set_generics(Some(generics.clone()));
 
+        // 示例与语法描述沿用用户书写的类型，不展示自动装箱
+        let example_items = patterns.collect_example();
+        let grammar = build_grammar(&name.to_string(), patterns);
+        let grammar_fn = generate_grammar_fn(&grammar, visibility);
+        let railroad_doc = generate_railroad_doc(&grammar);
+        let warnings = lint(patterns)
+            .iter()
+            .map(Lint::to_warning)
+            .collect::<Vec<_>>();
+        let patterns = &resolve_recursion(patterns.clone(), name, generics);
         let patterns_tokens = self.compile_pattern(patterns);
 
         let captures = patterns.collect_captures();
-        let example_items = patterns.collect_example();
+
+        let (capture_init, struct_def, struct_expr, _) =
+            generate_define_output(&captures, name.clone(), visibility.clone());
+        let finalize = generate_finalize(&captures);
+        let generics = define_generics();
+        let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+        let printer_impl = if is_printable(patterns) {
+            let scoped_definition = &self.scoped_definition;
+            let printer_tokens = self.compile_printer(patterns);
+            let body = quote! {
+                #(#scoped_definition)*
+                #printer_tokens
+            };
+            Self::generate_printer_impl(name, &generics, &captures, &struct_expr, &body)
+        } else {
+            vec![]
+        };
 
         let Compiler {
             shared_definition,
@@ -91,9 +250,6 @@ impl Compiler {
             ..
         } = &self;
 
-        let (capture_init, struct_def, struct_expr, _) =
-            generate_output(&captures, Some(name.clone()), Some(visibility.clone()));
-
         let (example_doc, extra) = generate_example(&example_items, false, false, false);
         let extra = extra.iter().map(|e| {
             quote! {
@@ -103,20 +259,94 @@ impl Compiler {
 
         tokens.extend(quote! {
             #(#shared_definition)*
+            #(#warnings)*
             #(#attrs)*
             #[doc = #example_doc]
             #(#extra)*
+            #railroad_doc
             #struct_def
-            impl ::syn::parse::Parse for #name {
+            impl #impl_generics ::syn::parse::Parse for #name #ty_generics #where_clause {
                 fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
                     #(#scoped_definition)*
                     #capture_init
                     #patterns_tokens
+                    #finalize
                     ::std::result::Result::Ok(#struct_expr)
                 }
             }
+            impl #impl_generics #name #ty_generics #where_clause {
+                #grammar_fn
+            }
+            #(#printer_impl)*
         });
         scope_context::set_scope_ident(None);
+        scope_context::set_generics(None);
+        tokens
+    }
+
+    /// `define!(enum Name { ... })`：直接生成多态枚举及其实现
+    fn compile_define_enum(&mut self, input: &DefineInput) -> TokenStream {
+        let DefineInput {
+            name,
+            patterns,
+            visibility,
+            attrs,
+            generics,
+            ..
+        } = input;
+
+        self.target = name.clone();
+        self.derive_attrs = attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("derive"))
+            .cloned()
+            .collect();
+        scope_context::set_scope_ident(Some(self.get_private_scope_ident()));
+        scope_context::set_generics(None);
+
+        // 整个输入即为各分支之一，语法描述中不再引用枚举自身
+        let mut grammar = build_grammar(&name.to_string(), patterns);
+        if let [Node::Choice { ty, .. }] = grammar.items.to_mut().as_mut_slice() {
+            *ty = None;
+        }
+        let grammar_fn = generate_grammar_fn(&grammar, visibility);
+        let railroad_doc = generate_railroad_doc(&grammar);
+        // 示例中只保留各分支的说明
+        let (_, extra) = generate_example(&patterns.collect_example(), false, false, false);
+        let warnings = lint(patterns)
+            .iter()
+            .map(Lint::to_warning)
+            .collect::<Vec<_>>();
+
+        let patterns = resolve_recursion(patterns.clone(), name, generics);
+        let PatternKind::Capture(capture) = &patterns.kind else {
+            unreachable!("enum form is parsed as a single capture");
+        };
+        let MatcherKind::Enum { variants, .. } = &capture.matcher.kind else {
+            unreachable!("enum form is parsed as an enum capture");
+        };
+        let enum_name: Type = parse_quote!(#name);
+        let variants_struct = self.generate_variant_struct(variants);
+        self.define_enum_parse_impl(variants, &enum_name);
+        if is_variants_printable(variants) {
+            self.define_enum_printer_impl(variants, &enum_name);
+        }
+
+        let shared_definition = &self.shared_definition;
+        let tokens = quote! {
+            #(#shared_definition)*
+            #(#warnings)*
+            #(#attrs)*
+            #(#[doc = #extra])*
+            #railroad_doc
+            #visibility enum #name {
+                #variants_struct
+            }
+            impl #name {
+                #grammar_fn
+            }
+        };
+        scope_context::set_scope_ident(None);
         tokens
     }
 }
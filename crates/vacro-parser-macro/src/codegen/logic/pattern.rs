@@ -3,10 +3,16 @@ use quote::quote;
 
 use crate::{
     ast::{
+        capture::{cfg_attrs, Binder, Capture},
         keyword::KeywordMap,
+        meta::SemanticInfo,
         node::{Pattern, PatternKind},
     },
-    codegen::{logic::Compiler, output::generate_output},
+    codegen::{
+        logic::Compiler,
+        output::{generate_output, output_ty},
+    },
+    utils::{proc_macro2_crate, resolve_crate_root},
 };
 
 impl Compiler {
@@ -45,6 +51,7 @@ impl Compiler {
                 let captures = pattern.collect_captures();
                 let (capture_init, struct_def, struct_expr, ..) =
                     generate_output(&captures, None, None);
+                let output_ty = output_ty(&captures);
 
                 // 追加到 body_stream
                 body_stream.extend(quote! {
@@ -52,7 +59,7 @@ impl Compiler {
                         #struct_def
                         let _input;
                         let _ = #mac(_input in input);
-                        let parser = |input: ::syn::parse::ParseStream| -> ::syn::Result<Output> {
+                        let parser = |input: ::syn::parse::ParseStream| -> ::syn::Result<#output_ty> {
                             #capture_init
                             #pattern_token
                             ::std::result::Result::Ok(#struct_expr)
@@ -65,21 +72,29 @@ impl Compiler {
                 let captures = capture.collect_captures();
                 let (capture_init, struct_def, struct_expr, ..) =
                     generate_output(&captures, None, None);
-                let cap_tokens = self.compile_capture(capture);
+                let output_ty = output_ty(&captures);
+                let mut cap_tokens = self.compile_capture(capture);
+                if let Some(meta) = &pattern.meta {
+                    cap_tokens = generate_semantic_error(meta, capture, cap_tokens);
+                }
+                // `#[cfg]` 关闭时跳过该捕获的解析
+                let cfg = cfg_attrs(&capture.attrs);
+                body_stream.extend(quote! { #(#cfg)* });
                 match &capture.edge {
                     Some(keyword) => {
                         // 3. Lookahead 逻辑，现在追加到 body_stream
+                        let proc_macro2 = proc_macro2_crate();
                         body_stream.extend(quote! {
                             {
-                                let mut _input = ::proc_macro2::TokenStream::new();
+                                let mut _input = #proc_macro2::TokenStream::new();
                                 while !input.peek(#keyword) {
                                     _input.extend(::std::iter::once(
-                                        input.parse::<::proc_macro2::TokenTree>()?
+                                        input.parse::<#proc_macro2::TokenTree>()?
                                     ));
                                 }
 
                                 #struct_def
-                                let parser = |input: ::syn::parse::ParseStream| -> ::syn::Result<Output> {
+                                let parser = |input: ::syn::parse::ParseStream| -> ::syn::Result<#output_ty> {
                                     #capture_init
                                     #cap_tokens
                                     ::std::result::Result::Ok(#struct_expr)
@@ -109,3 +124,52 @@ impl Compiler {
         tokens
     }
 }
+
+/// 带有语义信息的捕获：解析失败时改写为
+/// "expected <name> (<expectation>), e.g. `<example>`, found `<token>`"，并指向遇到的 token
+fn generate_semantic_error(
+    meta: &SemanticInfo,
+    capture: &Capture,
+    cap_tokens: TokenStream,
+) -> TokenStream {
+    let captures = capture.collect_captures();
+    let (capture_init, struct_def, struct_expr, ..) = generate_output(&captures, None, None);
+    let output_ty = output_ty(&captures);
+    let template = semantic_template(meta, &capture.binder);
+    let pkg = resolve_crate_root();
+    quote! {
+        #struct_def
+        let _begin = input.cursor();
+        let parser = |input: ::syn::parse::ParseStream| -> ::syn::Result<#output_ty> {
+            #capture_init
+            #cap_tokens
+            ::std::result::Result::Ok(#struct_expr)
+        };
+        #struct_expr = parser(input).map_err(|_err| {
+            let _found = _begin.token_tree().map(|(tt, _)| tt);
+            let _span = _found.as_ref().map_or_else(|| _err.span(), |tt| tt.span());
+            ::syn::Error::new(_span, #pkg::__private::semantic_message(#template, _found.as_ref()))
+        })?;
+    }
+}
+
+/// 错误模板，`{found}` 在运行时替换为实际遇到的 token
+fn semantic_template(meta: &SemanticInfo, binder: &Binder) -> String {
+    if let Some(custom_error) = &meta.custom_error {
+        return custom_error.clone();
+    }
+    let fallback = match binder {
+        Binder::Named(ident) => ident.to_string(),
+        _ => String::from("input"),
+    };
+    let mut template = match (&meta.name, &meta.expectation) {
+        (Some(name), Some(expectation)) => format!("expected {name} ({expectation})"),
+        (Some(what), None) | (None, Some(what)) => format!("expected {what}"),
+        (None, None) => format!("expected {fallback}"),
+    };
+    if let Some(example) = &meta.example {
+        template.push_str(&format!(", e.g. `{example}`"));
+    }
+    template.push_str(", found {found}");
+    template
+}
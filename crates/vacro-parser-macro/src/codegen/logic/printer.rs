@@ -0,0 +1,573 @@
+use proc_macro2::{Delimiter, Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::{parse_quote, Generics, Ident, Item, Type};
+
+use crate::{
+    ast::{
+        capture::{
+            alternation_item_ident, cfg_attrs, is_token_type, Binder, Capture, EnumVariant,
+            FieldDef, FieldDefault, Matcher, MatcherKind, Member, Quantity, RecordKind, Separator,
+        },
+        node::{Pattern, PatternKind},
+    },
+    codegen::logic::{capture::nested_item_ident, Compiler},
+    utils::{proc_macro2_crate, quote_crate},
+};
+
+/// 打印部分：将解析结果按模式顺序还原为 TokenStream（`ToTokens` 的函数体）
+///
+/// 生成的代码假定所有捕获字段都已作为同名的局部引用变量存在，
+/// 并且可以使用名为 `tokens` 的 `&mut proc_macro2::TokenStream`。
+impl Compiler {
+    pub fn compile_printer(&self, pattern: &Pattern) -> TokenStream {
+        let quote_crate = quote_crate();
+        match &pattern.kind {
+            PatternKind::Literal(keyword) => {
+                let definition = keyword.get_definition();
+                quote! {
+                    {
+                        #definition
+                        #quote_crate::ToTokens::to_tokens(
+                            &<#keyword as ::std::default::Default>::default(),
+                            tokens,
+                        );
+                    }
+                }
+            }
+            PatternKind::Group {
+                delimiter,
+                children,
+            } => {
+                let body: TokenStream = children.iter().map(|p| self.compile_printer(p)).collect();
+                let token = match delimiter {
+                    Delimiter::Brace => quote! { ::syn::token::Brace },
+                    Delimiter::Bracket => quote! { ::syn::token::Bracket },
+                    Delimiter::Parenthesis => quote! { ::syn::token::Paren },
+                    Delimiter::None => return body,
+                };
+                quote! {
+                    <#token as ::std::default::Default>::default().surround(tokens, |tokens| {
+                        #body
+                    });
+                }
+            }
+            PatternKind::Capture(capture) => {
+                let body = self.compile_capture_printer(capture);
+                match cfg_attrs(&capture.attrs)[..] {
+                    [] => body,
+                    ref cfg => quote! {
+                        #(#cfg)*
+                        {
+                            #body
+                        }
+                    },
+                }
+            }
+        }
+    }
+
+    fn compile_capture_printer(&self, capture: &Capture) -> TokenStream {
+        let quote_crate = quote_crate();
+        let Capture {
+            binder,
+            matcher,
+            quantity,
+            ..
+        } = capture;
+
+        let value = match binder {
+            Binder::Named(ident) => ident.clone(),
+            Binder::Inline(i) => format_ident!("_{}", i),
+            Binder::Anonymous => {
+                return match (quantity, &matcher.kind) {
+                    (Quantity::One, MatcherKind::Nested(patterns)) => {
+                        patterns.iter().map(|p| self.compile_printer(p)).collect()
+                    }
+                    (Quantity::Optional, MatcherKind::Nested(patterns)) => {
+                        self.compile_anonymous_optional_printer(patterns)
+                    }
+                    (Quantity::One, MatcherKind::Unordered { separator, members }) => {
+                        self.compile_unordered_printer(separator, members)
+                    }
+                    // `Token![...]` 不含值，重新构造即可
+                    (Quantity::One, MatcherKind::SynType(ty)) if is_token_type(ty) => quote! {
+                        #quote_crate::ToTokens::to_tokens(
+                            &<#ty as ::std::default::Default>::default(),
+                            tokens,
+                        );
+                    },
+                    // 其余匿名捕获不会保存解析结果，含有它们的模式不会生成打印实现
+                    _ => quote! {},
+                };
+            }
+        };
+
+        if let MatcherKind::Record { kind, children } = &matcher.kind {
+            return self.compile_record_printer(&value, *kind, children, quantity);
+        }
+
+        let element = self.compile_element_printer(binder, matcher);
+        if capture.is_flag() {
+            // 标记字段只记录是否出现，打印时重新构造该 Token 或字面量
+            let body = match &matcher.kind {
+                MatcherKind::Nested(children) => {
+                    children.iter().map(|p| self.compile_printer(p)).collect()
+                }
+                MatcherKind::SynType(ty) => quote! {
+                    let _v = &<#ty as ::std::default::Default>::default();
+                    #element
+                },
+                _ => unreachable!("flags are tokens or literals"),
+            };
+            return quote! {
+                if *#value {
+                    #body
+                }
+            };
+        }
+        // 带缺省值的字段总是存在
+        let quantity = match (quantity, &capture.default) {
+            (Quantity::Optional, Some(_)) => &Quantity::One,
+            _ => quantity,
+        };
+        match quantity {
+            Quantity::One => quote! {
+                let _v = #value;
+                #element
+            },
+            Quantity::Optional => quote! {
+                if let ::std::option::Option::Some(_v) = #value {
+                    #element
+                }
+            },
+            Quantity::Bounded { .. } if quantity.separator().is_some() => {
+                quote! {
+                    for (_v, _p) in #value.pairs().map(::syn::punctuated::Pair::into_tuple) {
+                        #element
+                        #quote_crate::ToTokens::to_tokens(&_p, tokens);
+                    }
+                }
+            }
+            Quantity::Bounded { .. } => quote! {
+                for _v in #value {
+                    #element
+                }
+            },
+            // 前瞻断言不消费输入，也不会被绑定
+            Quantity::Lookahead { .. } => quote! {},
+        }
+    }
+
+    /// 生成打印单个元素 `_v` 的代码
+    fn compile_element_printer(&self, binder: &Binder, matcher: &Matcher) -> TokenStream {
+        let quote_crate = quote_crate();
+        match &matcher.kind {
+            MatcherKind::Alternation(_, index) if matcher.kind.is_index_alternation() => {
+                // 分支索引无法实现外部 trait，使用私有的打印 trait
+                let item_ident = alternation_item_ident(binder, *index);
+                let printer_trait = format_ident!("_{}_ToTokens", item_ident);
+                if let Some(scope) = crate::scope_context::get_scope_ident() {
+                    quote! { <#scope::#item_ident as #scope::#printer_trait>::to_tokens(_v, tokens); }
+                } else {
+                    quote! { <#item_ident as #printer_trait>::to_tokens(_v, tokens); }
+                }
+            }
+            MatcherKind::SynType(_)
+            | MatcherKind::Enum { .. }
+            | MatcherKind::Named { .. }
+            | MatcherKind::Alternation(..) => {
+                quote! { #quote_crate::ToTokens::to_tokens(_v, tokens); }
+            }
+            MatcherKind::ParseWith { .. } => parse_with_printer(),
+            // 记录字段由 compile_record_printer 处理，无序组没有自己的字段
+            MatcherKind::Record { .. } | MatcherKind::Unordered { .. } => quote! {},
+            MatcherKind::Nested(children) => {
+                let captures: Vec<FieldDef> =
+                    children.iter().flat_map(|p| p.collect_captures()).collect();
+                if !is_tuple(&captures) {
+                    return quote! { #quote_crate::ToTokens::to_tokens(_v, tokens); };
+                }
+                // 元组类型无法实现外部 trait，使用私有的打印 trait
+                let item_ident = nested_item_ident(binder);
+                let printer_trait = format_ident!("_{}_ToTokens", item_ident);
+                if let Some(scope) = crate::scope_context::get_scope_ident() {
+                    quote! { <#scope::#item_ident as #scope::#printer_trait>::to_tokens(_v, tokens); }
+                } else {
+                    quote! { <#item_ident as #printer_trait>::to_tokens(_v, tokens); }
+                }
+            }
+        }
+    }
+
+    /// 记录子模式时打印其内部模式，分隔符记录使用捕获到的分隔符 (保留原有的 span)
+    fn compile_record_printer(
+        &self,
+        value: &Ident,
+        kind: RecordKind,
+        patterns: &[Pattern],
+        quantity: &Quantity,
+    ) -> TokenStream {
+        let body: TokenStream = patterns.iter().map(|p| self.compile_printer(p)).collect();
+        let body = match kind {
+            RecordKind::Delimiter(_) => quote! {
+                _r.surround(tokens, |tokens| {
+                    #body
+                });
+            },
+            RecordKind::Span | RecordKind::Tokens => body,
+        };
+        if let Quantity::Optional = quantity {
+            // 必需字段在外层被包裹为 Option，与记录字段同时存在
+            let required: Vec<_> = patterns
+                .iter()
+                .flat_map(|p| p.collect_captures())
+                .filter(|f| !f.is_optional && f.default.is_none())
+                .map(|f| f.name)
+                .collect();
+            quote! {
+                if let (
+                    ::std::option::Option::Some(_r),
+                    #(::std::option::Option::Some(#required),)*
+                ) = (#value, #(#required,)*) {
+                    #body
+                }
+            }
+        } else {
+            quote! {
+                let _r = #value;
+                #body
+            }
+        }
+    }
+
+    /// 无序组按声明顺序打印出现过的成员，成员之间插入分隔符
+    fn compile_unordered_printer(&self, separator: &Separator, members: &[Member]) -> TokenStream {
+        let proc_macro2 = proc_macro2_crate();
+        let separator = self.compile_printer(&Pattern {
+            kind: PatternKind::Literal(separator.keyword.clone()),
+            span: Span::call_site(),
+            meta: None,
+        });
+        let members = members.iter().map(|member| {
+            let body = self.compile_printer(&member.pattern);
+            quote! {
+                {
+                    let mut _member = #proc_macro2::TokenStream::new();
+                    {
+                        let tokens = &mut _member;
+                        #body
+                    }
+                    if !_member.is_empty() {
+                        if !_first {
+                            #separator
+                        }
+                        _first = false;
+                        tokens.extend(_member);
+                    }
+                }
+            }
+        });
+        quote! {
+            let mut _first = true;
+            #(#members)*
+        }
+    }
+
+    /// 匿名可选组没有自己的字段，根据其内部字段是否存在来判断是否打印
+    fn compile_anonymous_optional_printer(&self, patterns: &[Pattern]) -> TokenStream {
+        let captures: Vec<FieldDef> = patterns.iter().flat_map(|p| p.collect_captures()).collect();
+        let body: TokenStream = patterns.iter().map(|p| self.compile_printer(p)).collect();
+
+        // 带缺省值的字段在外层被包裹后会被转换，不再是 Option
+        let required: Vec<_> = captures
+            .iter()
+            .filter(|f| !f.is_optional && f.default.is_none())
+            .map(|f| &f.name)
+            .collect();
+        if !required.is_empty() {
+            // 必需字段在外层被包裹为 Option，它们要么同时存在，要么同时缺失
+            return quote! {
+                if let (#(::std::option::Option::Some(#required),)*) = (#(#required,)*) {
+                    #body
+                }
+            };
+        }
+
+        let present: Vec<_> = captures
+            .iter()
+            .filter_map(|f| {
+                let name = &f.name;
+                match f.default {
+                    None => Some(quote! { #name.is_some() }),
+                    Some(FieldDefault::Flag) => Some(quote! { *#name }),
+                    Some(FieldDefault::Value { .. }) => None,
+                }
+            })
+            .collect();
+        if present.is_empty() {
+            if captures.is_empty() {
+                // 没有任何字段可以判断该组是否出现过
+                return quote! {};
+            }
+            // 只剩带缺省值的字段，总是打印
+            return body;
+        }
+        quote! {
+            if #(#present)||* {
+                #body
+            }
+        }
+    }
+
+    /// 为多态枚举实现 `quote::ToTokens`
+    pub fn define_enum_printer_impl(
+        &mut self,
+        variants: &[(EnumVariant, Matcher)],
+        enum_name: &Type,
+    ) {
+        let item = self.enum_printer_impl(variants, enum_name, &Generics::default());
+        self.shared_definition.push(item);
+    }
+
+    /// 生成枚举的 `quote::ToTokens` 实现
+    pub fn enum_printer_impl(
+        &self,
+        variants: &[(EnumVariant, Matcher)],
+        enum_name: &Type,
+        generics: &Generics,
+    ) -> Item {
+        let quote_crate = quote_crate();
+        let proc_macro2 = proc_macro2_crate();
+        let generics = printer_generics(generics);
+        let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+        let arms = variants.iter().map(|(v, _)| match v {
+            EnumVariant::Type { ident, .. } => quote! {
+                #enum_name::#ident(_v) => #quote_crate::ToTokens::to_tokens(_v, tokens),
+            },
+            EnumVariant::Capture { ident, pattern, .. } => {
+                let fields = pattern.collect_captures();
+                let named = fields.first().map(|f| !f.is_inline).unwrap_or(false);
+                let names = fields.iter().map(|f| &f.name);
+                let binding = if named {
+                    let cfgs = fields.iter().map(FieldDef::cfg_attrs);
+                    quote! { { #(#(#cfgs)* #names),* } }
+                } else if fields.is_empty() {
+                    quote! {}
+                } else {
+                    quote! { (#(#names),*) }
+                };
+                let body = self.compile_printer(pattern);
+                quote! {
+                    #enum_name::#ident #binding => {
+                        #body
+                    }
+                }
+            }
+        });
+        let arms: Vec<_> = arms.collect();
+        parse_quote! {
+            impl #impl_generics #quote_crate::ToTokens for #enum_name #ty_generics #where_clause {
+                fn to_tokens(&self, tokens: &mut #proc_macro2::TokenStream) {
+                    match self {
+                        #(#arms)*
+                    }
+                }
+            }
+        }
+    }
+
+    /// 为只有字面量分支的匿名选择生成打印实现：按分支索引打印对应的字面量
+    ///
+    /// `usize` 无法实现外部 trait，因此实现私有的 `_<Name>_ToTokens` trait。
+    pub fn index_printer_impl(&self, item_name: &Ident, patterns: &[&Pattern]) -> Vec<Item> {
+        let proc_macro2 = proc_macro2_crate();
+        let printer_trait = format_ident!("_{}_ToTokens", item_name);
+        let arms = patterns.iter().enumerate().map(|(i, pattern)| {
+            let body = self.compile_printer(pattern);
+            quote! {
+                #i => {
+                    #body
+                }
+            }
+        });
+        vec![
+            parse_quote! {
+                #[allow(non_camel_case_types)]
+                pub trait #printer_trait {
+                    fn to_tokens(_v: &usize, tokens: &mut #proc_macro2::TokenStream);
+                }
+            },
+            parse_quote! {
+                impl #printer_trait for #item_name {
+                    fn to_tokens(_v: &usize, tokens: &mut #proc_macro2::TokenStream) {
+                        match *_v {
+                            #(#arms)*
+                            _ => {}
+                        }
+                    }
+                }
+            },
+        ]
+    }
+
+    /// 为结构体（或元组别名）生成打印实现
+    ///
+    /// 结构体直接实现 `quote::ToTokens`；元组别名无法实现外部 trait，
+    /// 因此实现私有的 `_<Name>_ToTokens` trait。
+    pub fn generate_printer_impl(
+        item_name: &Ident,
+        generics: &Generics,
+        captures: &[FieldDef],
+        struct_expr: &TokenStream,
+        body: &TokenStream,
+    ) -> Vec<Item> {
+        let quote_crate = quote_crate();
+        let proc_macro2 = proc_macro2_crate();
+        let generics = printer_generics(generics);
+        let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+        let fn_body = quote! {
+            #[allow(unused_variables)]
+            let #struct_expr = self;
+            #body
+        };
+        if is_tuple(captures) {
+            let printer_trait = format_ident!("_{}_ToTokens", item_name);
+            vec![
+                parse_quote! {
+                    #[allow(non_camel_case_types)]
+                    pub trait #printer_trait {
+                        fn to_tokens(&self, tokens: &mut #proc_macro2::TokenStream);
+                    }
+                },
+                parse_quote! {
+                    impl #impl_generics #printer_trait for #item_name #ty_generics #where_clause {
+                        fn to_tokens(&self, tokens: &mut #proc_macro2::TokenStream) {
+                            #fn_body
+                        }
+                    }
+                },
+            ]
+        } else {
+            vec![parse_quote! {
+                impl #impl_generics #quote_crate::ToTokens for #item_name #ty_generics #where_clause {
+                    fn to_tokens(&self, tokens: &mut #proc_macro2::TokenStream) {
+                        #fn_body
+                    }
+                }
+            }]
+        }
+    }
+}
+
+/// 打印实现要求每个类型参数都实现 `ToTokens`
+pub fn printer_generics(generics: &Generics) -> Generics {
+    let quote_crate = quote_crate();
+    let mut generics = generics.clone();
+    let params: Vec<_> = generics.type_params().map(|t| t.ident.clone()).collect();
+    if params.is_empty() {
+        return generics;
+    }
+    let where_clause = generics.make_where_clause();
+    for param in params {
+        where_clause
+            .predicates
+            .push(parse_quote!(#param: #quote_crate::ToTokens));
+    }
+    generics
+}
+
+/// 模式能否按原样还原；不能还原时不生成打印实现，以免静默丢弃 token
+///
+/// 匿名捕获不保存解析结果，只有不含值的部分 (e.g. 字面量、`Token![...]`) 可以重新构造
+pub fn is_printable(pattern: &Pattern) -> bool {
+    match &pattern.kind {
+        PatternKind::Literal(_) => true,
+        PatternKind::Group { children, .. } => children.iter().all(is_printable),
+        PatternKind::Capture(capture) => is_capture_printable(capture),
+    }
+}
+
+/// 枚举的每个分支都能还原
+pub fn is_variants_printable(variants: &[(EnumVariant, Matcher)]) -> bool {
+    variants.iter().all(|(v, _)| match v {
+        EnumVariant::Type { .. } => true,
+        EnumVariant::Capture { pattern, .. } => is_printable(pattern),
+    })
+}
+
+fn is_capture_printable(capture: &Capture) -> bool {
+    let Capture {
+        binder,
+        matcher,
+        quantity,
+        ..
+    } = capture;
+    // 前瞻断言不消费输入，无需还原
+    if let Quantity::Lookahead { .. } = quantity {
+        return true;
+    }
+    if let Binder::Anonymous = binder {
+        let reproducible = match (quantity, &matcher.kind) {
+            (Quantity::One, MatcherKind::Nested(_) | MatcherKind::Unordered { .. }) => true,
+            (Quantity::One, MatcherKind::SynType(ty)) => is_token_type(ty),
+            // 匿名可选组根据内部字段判断是否出现过，没有字段时无法判断
+            (Quantity::Optional, MatcherKind::Nested(children)) => {
+                children.iter().any(|p| !p.collect_captures().is_empty())
+            }
+            _ => false,
+        };
+        if !reproducible {
+            return false;
+        }
+    }
+    match &matcher.kind {
+        MatcherKind::SynType(_) | MatcherKind::ParseWith { .. } => true,
+        MatcherKind::Nested(children)
+        | MatcherKind::Named { children, .. }
+        | MatcherKind::Record { children, .. } => children.iter().all(is_printable),
+        MatcherKind::Enum { variants, .. } | MatcherKind::Alternation(variants, _) => {
+            is_variants_printable(variants)
+        }
+        MatcherKind::Unordered { members, .. } => {
+            members.iter().all(|member| is_printable(&member.pattern))
+        }
+    }
+}
+
+fn is_tuple(captures: &[FieldDef]) -> bool {
+    captures.first().map(|f| f.is_inline).unwrap_or(false)
+}
+
+/// 自定义解析函数的结果未必实现 `ToTokens` (e.g. `Attribute::parse_outer` 得到 `Vec<Attribute>`)
+///
+/// 通过自动引用优先使用 `ToTokens`，否则逐个打印其元素
+fn parse_with_printer() -> TokenStream {
+    let quote_crate = quote_crate();
+    let proc_macro2 = proc_macro2_crate();
+    quote! {
+        {
+            struct _Print<'a, T>(&'a T);
+            trait _PrintTokens {
+                fn _print(&self, tokens: &mut #proc_macro2::TokenStream);
+            }
+            impl<T: #quote_crate::ToTokens> _PrintTokens for _Print<'_, T> {
+                fn _print(&self, tokens: &mut #proc_macro2::TokenStream) {
+                    #quote_crate::ToTokens::to_tokens(self.0, tokens);
+                }
+            }
+            trait _PrintIter {
+                fn _print(&self, tokens: &mut #proc_macro2::TokenStream);
+            }
+            impl<'a, T> _PrintIter for &_Print<'a, T>
+            where
+                &'a T: ::std::iter::IntoIterator,
+                <&'a T as ::std::iter::IntoIterator>::Item: #quote_crate::ToTokens,
+            {
+                fn _print(&self, tokens: &mut #proc_macro2::TokenStream) {
+                    #quote_crate::TokenStreamExt::append_all(tokens, self.0);
+                }
+            }
+            (&_Print(_v))._print(tokens);
+        }
+    }
+}
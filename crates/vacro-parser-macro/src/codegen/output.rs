@@ -1,8 +1,11 @@
 use proc_macro2::TokenStream;
 use quote::{format_ident, quote};
-use syn::{Ident, Visibility};
+use syn::{GenericParam, Generics, Ident, Type, Visibility};
 
-use crate::ast::capture::{ExampleItem, FieldDef};
+use crate::{
+    ast::capture::{ExampleItem, FieldDef, FieldDefault},
+    scope_context::{get_generics, used_generics},
+};
 
 type CaptureInit = TokenStream;
 type StructDef = TokenStream;
@@ -14,74 +17,193 @@ pub fn generate_output(
     ident: Option<Ident>,
     visibility: Option<Visibility>,
 ) -> (CaptureInit, StructDef, StructExpr, CaptureList) {
+    build_output(capture_list, ident, visibility, false)
+}
+
+/// `define!` 的顶层结构体：保留声明的全部泛型参数
+pub fn generate_define_output(
+    capture_list: &[FieldDef],
+    ident: Ident,
+    visibility: Visibility,
+) -> (CaptureInit, StructDef, StructExpr, CaptureList) {
+    build_output(capture_list, Some(ident), Some(visibility), true)
+}
+
+fn build_output(
+    capture_list: &[FieldDef],
+    ident: Option<Ident>,
+    visibility: Option<Visibility>,
+    is_define: bool,
+) -> (CaptureInit, StructDef, StructExpr, CaptureList) {
+    // 具名结构体为最终输出，带缺省值的字段使用转换后的类型
+    let is_final = ident.is_some();
     let ident = ident.unwrap_or_else(|| format_ident!("Output"));
     let visibility = visibility.unwrap_or(Visibility::Inherited);
     let mut capture_init = TokenStream::new();
 
     let is_inline = capture_list.first().map(|f| f.is_inline).unwrap_or(false);
 
-    capture_init.extend(capture_list.iter().map(
-        |FieldDef {
-             name,
-             ty,
-             is_optional,
-             ..
-         }| {
-            if *is_optional {
-                quote! {
-                    #[allow(unused)]
-                    let mut #name: #ty = ::std::option::Option::None;
-                }
-            } else {
-                quote! {
-                    let #name: #ty;
-                }
+    capture_init.extend(capture_list.iter().map(|field| {
+        let FieldDef {
+            name,
+            ty,
+            is_optional,
+            ..
+        } = field;
+        let cfg = field.cfg_attrs();
+        if *is_optional {
+            quote! {
+                #(#cfg)*
+                #[allow(unused)]
+                let mut #name: #ty = ::std::option::Option::None;
+            }
+        } else {
+            quote! {
+                #(#cfg)*
+                let #name: #ty;
             }
-        },
-    ));
+        }
+    }));
+
+    let generics = if is_define {
+        define_generics()
+    } else {
+        struct_generics(capture_list, is_final)
+    };
+    let (_, ty_generics, where_clause) = generics.split_for_impl();
 
     let mut struct_fields = TokenStream::new();
-    struct_fields.extend(capture_list.iter().map(
-        |FieldDef {
-             name,
-             ty,
-             is_inline,
-             ..
-         }| {
-            if *is_inline {
-                quote! { #ty, }
-            } else {
-                quote! { pub #name: #ty,}
-            }
-        },
-    ));
+    struct_fields.extend(capture_list.iter().map(|field| {
+        let name = &field.name;
+        let ty = field_ty(field, is_final);
+        let attrs = &field.attrs;
+        if field.is_inline {
+            quote! { #ty, }
+        } else {
+            quote! { #(#attrs)* pub #name: #ty,}
+        }
+    }));
 
     let mut struct_expr_fields = TokenStream::new();
     let capture_ident_list: Vec<Ident> = capture_list
         .iter()
         .map(|FieldDef { name, .. }| name.clone())
         .collect();
-    struct_expr_fields.extend(capture_ident_list.iter().map(|ident| {
-        quote! {#ident,}
+    struct_expr_fields.extend(capture_list.iter().map(|field| {
+        let (name, cfg) = (&field.name, field.cfg_attrs());
+        quote! {#(#cfg)* #name,}
     }));
 
+    // 未被字段使用的泛型参数由标记字段占用，`PhantomData` 同时可作为表达式与模式
+    if is_define && !is_inline {
+        if let Some(marker_ty) = phantom_ty(capture_list) {
+            struct_fields.extend(quote! {
+                #[doc(hidden)]
+                pub _marker: #marker_ty,
+            });
+            struct_expr_fields.extend(quote! {
+                _marker: ::std::marker::PhantomData,
+            });
+        }
+    }
+
     if is_inline {
         (
             capture_init,
-            quote! { #visibility type #ident = (#struct_fields); },
+            quote! { #visibility type #ident #ty_generics = (#struct_fields); },
             quote! { (#struct_expr_fields) },
             capture_ident_list,
         )
     } else {
         (
             capture_init,
-            quote! { #visibility struct #ident { #struct_fields } },
+            quote! { #visibility struct #ident #generics #where_clause { #struct_fields } },
             quote! { #ident { #struct_expr_fields } },
             capture_ident_list,
         )
     }
 }
 
+fn field_ty(field: &FieldDef, is_final: bool) -> Type {
+    if is_final {
+        field.public_ty()
+    } else {
+        field.ty.clone()
+    }
+}
+
+/// 结构体的泛型参数：只保留字段类型中用到的 `define!` 泛型参数
+pub fn struct_generics(capture_list: &[FieldDef], is_final: bool) -> Generics {
+    let tys = capture_list.iter().map(|field| field_ty(field, is_final));
+    used_generics(quote! { #(#tys)* })
+}
+
+/// `define!` 顶层结构体的泛型参数：即用户声明的全部参数
+pub fn define_generics() -> Generics {
+    get_generics().unwrap_or_default()
+}
+
+/// 顶层结构体中未被字段使用的类型与生命周期参数组成的标记类型 (e.g. `PhantomData<(fn() -> T,)>`)
+fn phantom_ty(capture_list: &[FieldDef]) -> Option<Type> {
+    let all = get_generics()?;
+    let used = struct_generics(capture_list, true);
+    let ident = |param: &GenericParam| match param {
+        GenericParam::Type(t) => t.ident.clone(),
+        GenericParam::Lifetime(l) => l.lifetime.ident.clone(),
+        GenericParam::Const(c) => c.ident.clone(),
+    };
+    let is_used = |param: &GenericParam| used.params.iter().any(|p| ident(p) == ident(param));
+    let markers: Vec<TokenStream> = all
+        .params
+        .iter()
+        .filter(|param| !is_used(param))
+        .filter_map(|param| match param {
+            // `fn() -> T` 不影响 `Send`/`Sync` 等自动 trait
+            GenericParam::Type(t) => {
+                let ident = &t.ident;
+                Some(quote! { fn() -> #ident })
+            }
+            GenericParam::Lifetime(l) => {
+                let lifetime = &l.lifetime;
+                Some(quote! { &#lifetime () })
+            }
+            // 未使用的常量参数是允许的
+            GenericParam::Const(_) => None,
+        })
+        .collect();
+    if markers.is_empty() {
+        return None;
+    }
+    Some(syn::parse_quote! { ::std::marker::PhantomData<(#(#markers,)*)> })
+}
+
+/// 临时结构体 `Output` 的类型 (e.g. `Output<T>`)
+pub fn output_ty(capture_list: &[FieldDef]) -> TokenStream {
+    let generics = struct_generics(capture_list, false);
+    let (_, ty_generics, _) = generics.split_for_impl();
+    quote! { Output #ty_generics }
+}
+
+/// 构造最终结构体前，将带缺省值的字段从 `Option` 转换为公开类型
+pub fn generate_finalize(capture_list: &[FieldDef]) -> TokenStream {
+    capture_list
+        .iter()
+        .filter_map(|field| {
+            let (name, cfg) = (&field.name, field.cfg_attrs());
+            match field.default()? {
+                FieldDefault::Value { expr, .. } => Some(quote! {
+                    #(#cfg)*
+                    let #name = #name.unwrap_or_else(|| #expr);
+                }),
+                FieldDefault::Flag => Some(quote! {
+                    #(#cfg)*
+                    let #name = #name.is_some();
+                }),
+            }
+        })
+        .collect()
+}
+
 pub fn generate_example(
     example_items: &[ExampleItem],
     is_block: bool,
@@ -102,6 +224,7 @@ pub fn generate_example(
             }
             ExampleItem::Block {
                 optional,
+                repeat,
                 example,
                 iter,
             } => {
@@ -109,10 +232,10 @@ pub fn generate_example(
                 extra_example.append(&mut extra);
                 let suffix = format!(
                     "{}{}",
-                    if iter.is_empty() {
-                        String::new()
-                    } else {
-                        format!("{} ...", iter)
+                    match repeat {
+                        None => String::new(),
+                        Some(bounds) if iter.is_empty() => format!(" ...{}", bounds),
+                        Some(bounds) => format!("{} ...{}", iter, bounds),
                     },
                     if *optional { "?" } else { "" }
                 );
@@ -208,6 +331,8 @@ mod tests {
             ty: syn::parse_str(ty).unwrap(),
             is_optional,
             is_inline,
+            default: None,
+            attrs: Vec::new(),
         }
     }
 
@@ -1,21 +1,64 @@
-use proc_macro::TokenStream;
-use syn::parse_macro_input;
+use proc_macro2::TokenStream;
 
 use crate::{
-    ast::input::{BindInput, DefineInput},
+    ast::input::{BindInput, DefineInput, MatchInput},
     codegen::logic::Compiler,
+    scope_context::reset_inline_counter,
 };
 
-pub fn bind_impl(input: TokenStream) -> TokenStream {
-    let mut compiler = Compiler::new();
-    let capture_input = parse_macro_input!(input as BindInput);
+// 每次展开都从这里进入：先重置编号，再解析输入，保证同一调用总是展开为相同的标识符
 
-    compiler.compile_capture_input(&capture_input).into()
+pub fn bind_impl(input: TokenStream) -> TokenStream {
+    reset_inline_counter();
+    match syn::parse2::<BindInput>(input) {
+        Ok(capture_input) => Compiler::new().compile_capture_input(&capture_input),
+        Err(err) => err.to_compile_error(),
+    }
 }
 
 pub fn define_impl(input: TokenStream) -> TokenStream {
-    let mut compiler = Compiler::new();
-    let define_input = parse_macro_input!(input as DefineInput);
+    reset_inline_counter();
+    match syn::parse2::<DefineInput>(input) {
+        Ok(define_input) => Compiler::new().compile_define_input(&define_input),
+        Err(err) => err.to_compile_error(),
+    }
+}
+
+pub fn match_tokens_impl(input: TokenStream) -> TokenStream {
+    reset_inline_counter();
+    match syn::parse2::<MatchInput>(input) {
+        Ok(match_input) => Compiler::compile_match_input(&match_input),
+        Err(err) => err.to_compile_error(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quote::quote;
+
+    use super::*;
 
-    compiler.compile_define_input(&define_input).into()
+    #[test]
+    fn test_deterministic_expansion() {
+        let expand = || {
+            define_impl(quote! {
+                Item: #(@: Ident) ~> #(@: #(@: Ident) qux) #(kind: Kind {
+                    A: alpha #(a: Ident), B: beta #(b: Ident), C: gamma #(c: Ident),
+                    D: delta #(d: Ident), E: epsilon #(e: Ident), F: zeta #(f: Ident),
+                })
+            })
+            .to_string()
+        };
+        let first = expand();
+        // 其他调用消耗的计数不影响之后的展开
+        let others = [
+            bind_impl(quote! { let res = (t -> #(@: Ident) #(@: Ident) #(@: a | b)); }),
+            match_tokens_impl(quote! { t { #(@: Ident) #(name: Ident) => {}, _ => {} } }),
+        ];
+        assert!(others
+            .iter()
+            .all(|output| !output.to_string().contains("compile_error")));
+        assert_eq!(first, expand());
+        assert!(first.contains("_0"));
+    }
 }
@@ -1,7 +1,7 @@
 use proc_macro::TokenStream;
 use vacro_doc_i18n::doc_i18n;
 
-use crate::impls::{bind_impl, define_impl};
+use crate::impls::{bind_impl, define_impl, match_tokens_impl};
 
 pub(crate) mod ast;
 pub(crate) mod codegen;
@@ -26,10 +26,12 @@ mod utils;
 ///
 /// ```text
 /// vacro::bind!(let <绑定模式> = (<输入流> -> <Vacro模式>) <后续操作>);
+/// vacro::bind!(let <绑定模式> = (<输入流> => <Vacro模式>) <后续操作>);
 /// ```
 ///
 /// * **绑定模式**: 标准 Rust 模式，用于接收解析成功后的内容（如变量名 `res` 或元组 `(a, b)`）。
-/// * **输入流**: 实现了 `Into<TokenStream>` 的变量。
+/// * **输入流**: 实现了 `Into<TokenStream>` 的任意表达式，需被完全消费；
+///   使用 `=>` 代替 `->` 时为 `ParseStream`，只消费匹配的前缀，剩余部分留给后续代码。
 /// * **Vacro模式**: 描述语法的模式。
 /// * **后续操作**: 针对 `Result` 的操作，如 `?;`、`.unwrap();` 等。
 ///
@@ -85,10 +87,12 @@ mod utils;
 ///
 /// ```text
 /// vacro::bind!(let <binding> = (<input> -> <pattern>) <operations>);
+/// vacro::bind!(let <binding> = (<input> => <pattern>) <operations>);
 /// ```
 ///
 /// * **binding**: Standard Rust pattern to receive the parsed content (e.g., `res` or `(a, b)`).
-/// * **input**: A variable implementing `Into<TokenStream>`.
+/// * **input**: Any expression implementing `Into<TokenStream>`, which must be consumed entirely;
+///   with `=>` instead of `->` it is a `ParseStream`, of which only the matched prefix is consumed.
 /// * **pattern**: The Vacro pattern description.
 /// * **operations**: Operations on the `Result`, such as `?;`, `.unwrap();`, etc.
 ///
@@ -135,7 +139,7 @@ mod utils;
 /// :::
 #[proc_macro]
 pub fn bind(input: TokenStream) -> TokenStream {
-    bind_impl(input)
+    bind_impl(input.into()).into()
 }
 
 #[doc_i18n]
@@ -155,10 +159,13 @@ pub fn bind(input: TokenStream) -> TokenStream {
 /// 宏会自动生成：
 /// 1. `struct StructName { ... }`：包含所有**具名捕获**的字段。
 /// 2. `impl syn::parse::Parse for StructName { ... }`：包含解析逻辑。
+/// 3. `impl quote::ToTokens for StructName { ... }`：按模式顺序将结构体还原为 `TokenStream`。
 ///
 /// # 注意事项
 ///
 /// * `define!` 中通常使用**具名捕获** (`#(name: Type)`) 来生成结构体字段。
+/// * 匿名捕获 (`#(Type)`) 不会保存解析结果，无法被还原；含有此类捕获的结构体不会实现 `ToTokens`。
+///   字面量、`Token![...]` 以及前瞻断言不受影响。
 ///
 /// # 示例
 ///
@@ -204,10 +211,14 @@ pub fn bind(input: TokenStream) -> TokenStream {
 /// The macro automatically generates:
 /// 1. `struct StructName { ... }`: Containing all **named captured** fields.
 /// 2. `impl syn::parse::Parse for StructName { ... }`: Containing the parsing logic.
+/// 3. `impl quote::ToTokens for StructName { ... }`: Printing the struct back to a `TokenStream`
+///    in pattern order.
 ///
 /// # Notes
 ///
 /// * `define!` typically uses **Named Captures** (`#(name: Type)`) to generate struct fields.
+/// * Anonymous captures (`#(Type)`) do not store the parsed value and cannot be printed back;
+///   structs containing them do not implement `ToTokens`. Literals, `Token![...]` and lookaheads are unaffected.
 ///
 /// # Example
 ///
@@ -239,5 +250,95 @@ pub fn bind(input: TokenStream) -> TokenStream {
 /// :::
 #[proc_macro]
 pub fn define(input: TokenStream) -> TokenStream {
-    define_impl(input)
+    define_impl(input.into()).into()
+}
+
+#[doc_i18n]
+/// @cn 多分支匹配宏：类似 `macro_rules!`，依次尝试每个分支的模式
+/// @en Multi-arm matching macro: Tries each arm's pattern in order, like `macro_rules!`
+///
+/// ::: @cn
+///
+/// `match_tokens!` 按顺序尝试每个分支，第一个能完整解析输入的分支被选中。
+/// 分支中的捕获会作为局部变量绑定在分支体中，整个表达式的值为 `syn::Result<T>`。
+///
+/// # 语法
+///
+/// ```text
+/// vacro::match_tokens!(<输入流> {
+///     <Vacro模式> => <表达式>,
+///     ...
+///     _ => <表达式>,
+/// })
+/// ```
+///
+/// * **输入流**: 实现了 `Into<TokenStream>` 与 `Clone` 的表达式，每个分支都会完整解析它。
+/// * **分支**: 模式截止到顶层的 `=>`；模式中的 `=>` 字面量需放在分组中。
+/// * **`_`**: 可选的兜底分支，必须是最后一个分支。
+///
+/// 没有分支匹配且没有 `_` 分支时，返回合并了各分支错误的 `syn::Error`。
+///
+/// # 示例
+///
+/// ```rust
+/// # use syn::{Ident, Result, Type};
+/// # use vacro_parser::match_tokens;
+/// # use proc_macro2::TokenStream;
+/// fn describe(input: TokenStream) -> Result<String> {
+///     match_tokens!(input {
+///         fn #(name: Ident) => format!("function {name}"),
+///         struct #(name: Ident) { #(fields*[,]: #(field: Ident): #(ty: Type)) } => {
+///             format!("struct {name} with {} fields", fields.len())
+///         }
+///         _ => String::from("unknown"),
+///     })
+/// }
+/// # fn main() {}
+/// ```
+///
+/// :::
+/// ::: @en
+///
+/// `match_tokens!` tries each arm in order and picks the first one whose pattern parses the
+/// entire input. The arm's captures are bound as locals in its body, and the whole expression
+/// evaluates to `syn::Result<T>`.
+///
+/// # Syntax
+///
+/// ```text
+/// vacro::match_tokens!(<input> {
+///     <pattern> => <expr>,
+///     ...
+///     _ => <expr>,
+/// })
+/// ```
+///
+/// * **input**: An expression implementing `Into<TokenStream>` and `Clone`; every arm parses all of it.
+/// * **arm**: The pattern extends to the top-level `=>`; a literal `=>` in a pattern must be put in a group.
+/// * **`_`**: An optional fallback arm, which must come last.
+///
+/// When no arm matches and there is no `_` arm, the result is a `syn::Error` combining the errors of all arms.
+///
+/// # Example
+///
+/// ```rust
+/// # use syn::{Ident, Result, Type};
+/// # use vacro_parser::match_tokens;
+/// # use proc_macro2::TokenStream;
+/// fn describe(input: TokenStream) -> Result<String> {
+///     match_tokens!(input {
+///         fn #(name: Ident) => format!("function {name}"),
+///         struct #(name: Ident) { #(fields*[,]: #(field: Ident): #(ty: Type)) } => {
+///             format!("struct {name} with {} fields", fields.len())
+///         }
+///         _ => String::from("unknown"),
+///     })
+/// }
+/// # fn main() {}
+/// ```
+///
+/// :::
+#[proc_macro]
+pub fn match_tokens(input: TokenStream) -> TokenStream {
+    match_tokens_impl(input.into()).into()
 }
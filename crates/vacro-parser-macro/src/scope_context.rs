@@ -1,21 +1,33 @@
 use std::{
     cell::RefCell,
+    collections::HashSet,
     sync::atomic::{AtomicUsize, Ordering},
 };
-use syn::Ident;
+
+use proc_macro2::{TokenStream, TokenTree};
+use quote::ToTokens;
+use syn::{GenericParam, Generics, Ident, WhereClause};
 
 thread_local! {
     static SCOPE_IDENT: RefCell<Option<Ident>> = const { RefCell::new(None) };
+    static GENERICS: RefCell<Option<Generics>> = const { RefCell::new(None) };
     static INLINE_COUNTER: AtomicUsize = const { AtomicUsize::new(0) };
+    static ALTERNATION_COUNTER: AtomicUsize = const { AtomicUsize::new(0) };
 }
 
 pub fn next_inline_index() -> usize {
     INLINE_COUNTER.with(|i| i.fetch_add(1, Ordering::Relaxed))
 }
 
-#[cfg(test)]
+/// Numbers alternations in the order they appear in the invocation.
+pub fn next_alternation_index() -> usize {
+    ALTERNATION_COUNTER.with(|i| i.fetch_add(1, Ordering::Relaxed))
+}
+
+/// Restarts inline capture and alternation numbering, so each invocation expands to the same identifiers.
 pub fn reset_inline_counter() {
-    INLINE_COUNTER.with(|i| i.store(0, Ordering::SeqCst))
+    INLINE_COUNTER.with(|i| i.store(0, Ordering::SeqCst));
+    ALTERNATION_COUNTER.with(|i| i.store(0, Ordering::SeqCst));
 }
 
 /// Sets the current scope identifier.
@@ -27,3 +39,66 @@ pub fn set_scope_ident(ident: Option<Ident>) {
 pub fn get_scope_ident() -> Option<Ident> {
     SCOPE_IDENT.with(|f| f.borrow().clone())
 }
+
+/// Sets the generics declared on the `define!` struct.
+pub fn set_generics(generics: Option<Generics>) {
+    GENERICS.with(|f| *f.borrow_mut() = generics);
+}
+
+/// Gets the generics declared on the `define!` struct, if any.
+pub fn get_generics() -> Option<Generics> {
+    GENERICS.with(|f| f.borrow().clone())
+}
+
+/// Keeps only the generic parameters (and where predicates) mentioned by `tokens`.
+///
+/// Nested items only declare the parameters their fields use, so no parameter is left unused.
+pub fn used_generics(tokens: impl ToTokens) -> Generics {
+    let Some(generics) = get_generics() else {
+        return Generics::default();
+    };
+    let mut idents = HashSet::new();
+    collect_idents(tokens.into_token_stream(), &mut idents);
+
+    let param_ident = |param: &GenericParam| match param {
+        GenericParam::Type(t) => t.ident.clone(),
+        GenericParam::Lifetime(l) => l.lifetime.ident.clone(),
+        GenericParam::Const(c) => c.ident.clone(),
+    };
+    let (used, unused): (Vec<_>, Vec<_>) = generics
+        .params
+        .iter()
+        .cloned()
+        .partition(|param| idents.contains(&param_ident(param)));
+    let unused: HashSet<_> = unused.iter().map(param_ident).collect();
+
+    let where_clause = generics.where_clause.map(|where_clause| WhereClause {
+        predicates: where_clause
+            .predicates
+            .into_iter()
+            .filter(|predicate| {
+                let mut idents = HashSet::new();
+                collect_idents(predicate.to_token_stream(), &mut idents);
+                idents.is_disjoint(&unused)
+            })
+            .collect(),
+        ..where_clause
+    });
+    Generics {
+        params: used.into_iter().collect(),
+        where_clause: where_clause.filter(|w| !w.predicates.is_empty()),
+        ..generics
+    }
+}
+
+fn collect_idents(tokens: TokenStream, idents: &mut HashSet<Ident>) {
+    for tt in tokens {
+        match tt {
+            TokenTree::Ident(ident) => {
+                idents.insert(ident);
+            }
+            TokenTree::Group(group) => collect_idents(group.stream(), idents),
+            _ => {}
+        }
+    }
+}
@@ -2,4 +2,5 @@ pub mod capture;
 pub mod context;
 pub mod input;
 pub mod keyword;
+pub mod meta;
 pub mod pattern;
@@ -1,20 +1,24 @@
-use proc_macro2::{Delimiter, TokenStream, TokenTree};
-use quote::TokenStreamExt;
+use proc_macro2::{Delimiter, Spacing, Span, TokenStream, TokenTree};
+use quote::{format_ident, TokenStreamExt};
 use syn::{
     braced, bracketed, parenthesized,
     parse::{discouraged::Speculative, Parse, ParseStream, Parser},
     parse_quote,
     spanned::Spanned,
-    token, Ident, Path, Token, Type,
+    token, Attribute, Expr, Ident, LitInt, LitStr, Path, Token, Type, Visibility,
 };
 
 use crate::{
     ast::{
-        capture::{Binder, Capture, EnumVariant, Matcher, MatcherKind, Quantity},
+        capture::{
+            cfg_attrs, is_flag_marker, Binder, Capture, EnumVariant, FieldDef, Matcher,
+            MatcherKind, Member, NamedItem, Quantity, RecordKind, Separator, Trailing,
+        },
         keyword::Keyword,
+        meta::SemanticInfo,
         node::{Pattern, PatternKind},
     },
-    scope_context::next_inline_index,
+    scope_context::{next_alternation_index, next_inline_index},
     syntax::context::ParseContext,
 };
 
@@ -25,10 +29,49 @@ impl Capture {
         let start_span = _hash_tag.span;
         let content;
         let _paren = parenthesized!(content in input);
+        // 只消费 `#[...]`，其余以 `#` 开头的内容交由后续解析报错
+        let mut attrs = Vec::new();
+        while content.peek(Token![#]) && content.peek2(token::Bracket) {
+            attrs.extend(content.call(Attribute::parse_outer)?);
+        }
 
         let lookahead = content.lookahead1();
         let fork = content.fork();
-        if fork.parse::<Type>().is_ok() && fork.is_empty() {
+        let is_type = fork.parse::<Type>().is_ok();
+        let capture = if (is_type && fork.peek(Token![|])) || is_alternation(&content)? {
+            // 匿名选择 <A | B>，仅作验证
+            let matcher = Matcher::parse_choice(&content, ctx)?;
+            check_anonymous_alternation(&matcher)?;
+            let span = start_span.join(matcher.span).unwrap_or(start_span);
+            Ok(Capture {
+                _hash_tag,
+                _paren,
+                attrs: Vec::new(),
+                matcher,
+                quantity: Quantity::One,
+                binder: Binder::Anonymous,
+                default: None,
+                validator: None,
+                edge: None,
+                span,
+            })
+        } else if is_type && peek_with(&fork) {
+            // 匿名捕获 <Capture> 类型，使用自定义解析函数
+            let matcher = Matcher::parse(&content, ctx)?;
+            let span = start_span.join(matcher.span).unwrap_or(start_span);
+            Ok(Capture {
+                _hash_tag,
+                _paren,
+                attrs: Vec::new(),
+                matcher,
+                quantity: Quantity::One,
+                binder: Binder::Anonymous,
+                default: None,
+                validator: None,
+                edge: None,
+                span,
+            })
+        } else if is_type && fork.is_empty() {
             // 匿名捕获 <Capture> 类型
             let ty: Type = content.parse()?;
             let end_span = ty.span();
@@ -41,9 +84,12 @@ impl Capture {
             Ok(Capture {
                 _hash_tag,
                 _paren,
+                attrs: Vec::new(),
                 matcher,
                 quantity,
                 binder,
+                default: None,
+                validator: None,
                 edge: None,
 
                 span: start_span.join(end_span).unwrap_or(start_span),
@@ -51,6 +97,7 @@ impl Capture {
         } else if lookahead.peek(Ident) || lookahead.peek(Token![@]) {
             // 具名捕获 <name: Capture> 与 行内捕获 <@: Capture> 及变体
 
+            let binder_span = content.span();
             let binder = if lookahead.peek(Ident) {
                 let ident: Ident = content.parse()?;
                 Binder::Named(ident)
@@ -59,81 +106,238 @@ impl Capture {
                 let i = next_inline_index();
                 Binder::Inline(i)
             };
-            let mut quantity = Quantity::One;
-            if content.peek(Token![?]) {
-                quantity = Quantity::Optional;
-                content.parse::<Token![?]>()?;
-            } else if content.peek(Token![*]) {
-                content.parse::<Token![*]>()?;
-                if content.peek(token::Bracket) {
-                    let separator_tokens;
-                    let _br = bracketed!(separator_tokens in content);
-                    if separator_tokens.is_empty() {
-                        return Err(syn::Error::new(
-                            separator_tokens.span(),
-                            "expected '[<separator>]' like '[,]'",
-                        ));
-                    }
-                    let separater = Keyword::parse(&separator_tokens, ctx)?;
-                    quantity = Quantity::Many(Some(separater));
-                } else {
-                    return Err(content.error("expected '[<separator>]' like '[,]'"));
-                };
+            let quantity = Quantity::parse(&content, ctx)?;
+            if let Quantity::Lookahead { .. } = quantity {
+                return Err(syn::Error::new(
+                    binder_span,
+                    "lookahead assertions cannot be bound, use '#(&: ...)' or '#(!: ...)'",
+                ));
             }
             if content.peek(Token![:]) {
                 let _colon = content.parse::<Token![:]>()?;
-                let matcher = Matcher::parse(&content, ctx)?;
-                let end_span = matcher.span;
+                // 只有单次捕获 `#(x: A | B)` 中顶层的 `|` 才是选择分支
+                let parse_matcher = if matches!(quantity, Quantity::One) {
+                    Matcher::parse_choice
+                } else {
+                    Matcher::parse
+                };
+                let (matcher, default, validator) = match split_clauses(&content)? {
+                    Some((tokens, default, validator)) => {
+                        let parser = |input: ParseStream| parse_matcher(input, ctx);
+                        (parser.parse2(tokens)?, default, validator)
+                    }
+                    None => (parse_matcher(&content, ctx)?, None, None),
+                };
+                check_record(&matcher, &quantity)?;
+                if let (MatcherKind::Record { .. }, Quantity::Optional) = (&matcher.kind, &quantity)
+                {
+                    check_cfg(
+                        &matcher.collect_captures(&Binder::Anonymous),
+                        "an optional record",
+                    )?;
+                }
+                if let Some(default) = &default {
+                    check_default(default, &matcher, &quantity)?;
+                }
+                let end_span = validator.as_ref().map(|v| v.span()).unwrap_or(matcher.span);
                 Ok(Capture {
                     _hash_tag,
                     _paren,
+                    attrs: Vec::new(),
                     binder,
                     matcher,
                     quantity,
+                    default,
+                    validator,
                     edge: None,
                     span: start_span.join(end_span).unwrap_or(start_span),
                 })
             } else {
                 Err(content.error("expected ':' after capture name"))
             }
-        } else {
-            let mut quantity = Quantity::One;
-            if content.peek(Token![?]) {
-                quantity = Quantity::Optional;
-                content.parse::<Token![?]>()?;
-            } else if content.peek(Token![*]) {
-                content.parse::<Token![*]>()?;
-                if content.peek(token::Bracket) {
-                    let separater_tokens;
-                    let _br = bracketed!(separater_tokens in content);
-                    if separater_tokens.is_empty() {
-                        return Err(separater_tokens.error("expected '[<separator>]' like '[,]'"));
-                    }
-                    let separater = Keyword::parse(&separater_tokens, ctx)?;
-                    quantity = Quantity::Many(Some(separater));
-                } else {
-                    return Err(content.error("expected '[<separator>]' like '[,]'"));
-                };
+        } else if content.peek(Token![&]) && content.peek2(token::Bracket) {
+            // 无序组 <&[sep]: A | B | ...>
+            let _and = content.parse::<Token![&]>()?;
+            let separator_tokens;
+            let _br = bracketed!(separator_tokens in content);
+            if separator_tokens.is_empty() {
+                return Err(syn::Error::new(
+                    separator_tokens.span(),
+                    "expected '[<separator>]' like '[,]'",
+                ));
             }
+            let separator = Separator::parse(&separator_tokens, ctx)?;
+            let _colon = content.parse::<Token![:]>()?;
+            let matcher = Matcher::parse_unordered(&content, separator)?;
+            check_cfg(
+                &matcher.collect_captures(&Binder::Anonymous),
+                "an unordered group",
+            )?;
+            let span = start_span.join(matcher.span).unwrap_or(start_span);
+            Ok(Capture {
+                _hash_tag,
+                _paren,
+                attrs: Vec::new(),
+                matcher,
+                quantity: Quantity::One,
+                binder: Binder::Anonymous,
+                default: None,
+                validator: None,
+                edge: None,
+                span,
+            })
+        } else {
+            let quantity = Quantity::parse(&content, ctx)?;
             let _colon = content.parse::<Token![:]>()?;
             let matcher = Matcher::parse(&content, ctx)?;
+            check_anonymous_alternation(&matcher)?;
+            if let MatcherKind::Record { .. } = matcher.kind {
+                return Err(syn::Error::new(
+                    matcher.span,
+                    "span(...), tokens(...) and delimiter records need a name like '#(name: span(...))'",
+                ));
+            }
+            if let Quantity::Lookahead { .. } = quantity {
+                check_lookahead(&matcher)?;
+            }
+            if !matches!(quantity, Quantity::One) {
+                check_cfg(
+                    &matcher.collect_captures(&Binder::Anonymous),
+                    "an anonymous optional or repeated group",
+                )?;
+            }
             let end_span = matcher.span;
             Ok(Capture {
                 _hash_tag,
                 _paren,
+                attrs: Vec::new(),
                 quantity,
                 matcher,
                 binder: Binder::Anonymous,
+                default: None,
+                validator: None,
                 edge: None,
                 span: start_span.join(end_span).unwrap_or(start_span),
             })
+        }?;
+        check_attrs(&attrs, &capture.binder, &capture.matcher)?;
+        Ok(Capture { attrs, ..capture })
+    }
+}
+
+/// 数量限定 `?`、`*`、`+`、`{n}`、`{n,}`、`{n,m}`，重复捕获可带 `[sep]`、`[sep!]` 或 `[sep+]`；
+/// 以及前瞻断言 `&`、`!`
+impl Quantity {
+    pub fn parse(input: syn::parse::ParseStream, ctx: &mut ParseContext) -> syn::Result<Self> {
+        if input.peek(Token![&]) {
+            input.parse::<Token![&]>()?;
+            return Ok(Quantity::Lookahead { negative: false });
+        }
+        if input.peek(Token![!]) {
+            input.parse::<Token![!]>()?;
+            return Ok(Quantity::Lookahead { negative: true });
+        }
+        if input.peek(Token![?]) {
+            input.parse::<Token![?]>()?;
+            return Ok(Quantity::Optional);
+        }
+        let (min, max) = if input.peek(Token![*]) {
+            input.parse::<Token![*]>()?;
+            (0, None)
+        } else if input.peek(Token![+]) {
+            input.parse::<Token![+]>()?;
+            (1, None)
+        } else if input.peek(token::Brace) {
+            parse_bounds(input)?
+        } else {
+            return Ok(Quantity::One);
+        };
+
+        let separator = if input.peek(token::Bracket) {
+            let separator_tokens;
+            let _br = bracketed!(separator_tokens in input);
+            if separator_tokens.is_empty() {
+                return Err(syn::Error::new(
+                    separator_tokens.span(),
+                    "expected '[<separator>]' like '[,]'",
+                ));
+            }
+            Some(Separator::parse(&separator_tokens, ctx)?)
+        } else {
+            // 无分隔符的重复，生成 Vec<T>
+            None
+        };
+
+        Ok(Quantity::Bounded {
+            separator,
+            min,
+            max,
+        })
+    }
+}
+
+/// 分隔符 `[sep]`，可在分隔符后标记末尾分隔符策略：`[sep!]` 禁止，`[sep+]` 必须
+impl Separator {
+    pub fn parse(input: ParseStream, ctx: &mut ParseContext) -> syn::Result<Self> {
+        let keyword = Keyword::parse(input, ctx)?;
+        let trailing = if input.is_empty() {
+            Trailing::Optional
+        } else if input.peek(Token![!]) {
+            input.parse::<Token![!]>()?;
+            Trailing::Forbidden
+        } else if input.peek(Token![+]) {
+            input.parse::<Token![+]>()?;
+            Trailing::Required
+        } else {
+            return Err(input.error("expected '!' or '+' after the separator"));
+        };
+        if !input.is_empty() {
+            return Err(input.error("expected ']' after the separator"));
         }
+        Ok(Separator { keyword, trailing })
+    }
+}
+
+/// 解析 `{n}`、`{n,}` 或 `{n,m}`
+fn parse_bounds(input: ParseStream) -> syn::Result<(usize, Option<usize>)> {
+    let content;
+    let brace = braced!(content in input);
+    let min: LitInt = content.parse()?;
+    let min = min.base10_parse::<usize>()?;
+    let max = if content.is_empty() {
+        Some(min)
+    } else {
+        content.parse::<Token![,]>()?;
+        if content.is_empty() {
+            None
+        } else {
+            let max: LitInt = content.parse()?;
+            Some(max.base10_parse::<usize>()?)
+        }
+    };
+    if !content.is_empty() {
+        return Err(content.error("expected '{n}', '{n,}' or '{n,m}'"));
+    }
+    match max {
+        Some(0) => Err(syn::Error::new(
+            brace.span.join(),
+            "the maximum number of repetitions must be greater than 0",
+        )),
+        Some(max) if max < min => Err(syn::Error::new(
+            brace.span.join(),
+            format!("invalid repetition bounds: {min} is greater than {max}"),
+        )),
+        _ => Ok((min, max)),
     }
 }
 
 impl Matcher {
     pub fn parse(input: syn::parse::ParseStream, ctx: &mut ParseContext) -> syn::Result<Self> {
-        let cap = if input.peek(Token![#]) {
+        if let Some(item) = NamedItem::parse_prefix(input)? {
+            return Matcher::parse_named(input, item, ctx);
+        }
+        // `#[expect(...)] #(...)` 交给 Pattern::parse 处理
+        let cap = if input.peek(Token![#]) && !SemanticInfo::peek_expect(input) {
             // #{...} 字面量捕获：将大括号内容作为字面量模式
             if input.peek2(token::Brace) {
                 let _hash: Token![#] = input.parse()?;
@@ -173,7 +377,8 @@ impl Matcher {
             // 如果是 #(...)，则解析为 Capture
             let capture = Capture::parse(input, ctx)?;
             let span = capture.span;
-            if let MatcherKind::Enum { .. } = &capture.matcher.kind {
+            let meta = SemanticInfo::parse_name(input, None)?;
+            if let (MatcherKind::Enum { .. }, None) = (&capture.matcher.kind, &meta) {
                 Matcher {
                     kind: capture.matcher.kind,
                     span,
@@ -182,14 +387,41 @@ impl Matcher {
                 let pattern = Pattern {
                     kind: PatternKind::Capture(Box::new(capture)),
                     span,
-                    meta: None,
+                    meta,
                 };
                 Matcher {
                     kind: MatcherKind::Nested(vec![pattern]),
                     span,
                 }
             }
-        } else if input.peek(Ident) {
+        } else if (input.peek(Ident) || input.peek(Token![Self])) && !peek_literal_as(input) {
+            if input.peek(Ident) && input.peek2(token::Paren) {
+                if let Some(kind) = RecordKind::from_ident(&input.fork().parse()?) {
+                    // span(...)、tokens(...)、Paren(...) 等记录子模式
+                    let ident: Ident = input.parse()?;
+                    let start_span = ident.span();
+                    let content;
+                    let _paren = parenthesized!(content in input);
+                    let inner = Pattern::parse(&content)?;
+                    let end_span = _paren.span.close();
+                    let children = if let PatternKind::Group { children, .. } = inner.kind {
+                        children
+                    } else {
+                        vec![inner]
+                    };
+                    let matcher = Matcher {
+                        kind: MatcherKind::Record { kind, children },
+                        span: start_span.join(end_span).unwrap_or(start_span),
+                    };
+                    if !input.is_empty() {
+                        return Err(syn::Error::new(
+                            input.span(),
+                            format!("Unexpected '{}'", input),
+                        ));
+                    }
+                    return Ok(matcher);
+                }
+            }
             if input.peek2(token::Brace) {
                 let enum_name: Type = input.parse()?;
                 let start_span = enum_name.span();
@@ -211,6 +443,14 @@ impl Matcher {
                             Matcher {
                                 span: v.span(),
                                 kind: match &v {
+                                    EnumVariant::Type {
+                                        ty,
+                                        parser: Some(parser),
+                                        ..
+                                    } => MatcherKind::ParseWith {
+                                        ty: ty.clone(),
+                                        parser: (**parser).clone(),
+                                    },
                                     EnumVariant::Type { ty, .. } => {
                                         MatcherKind::SynType(ty.clone())
                                     }
@@ -232,9 +472,17 @@ impl Matcher {
             }
             let ty: Type = input.parse()?;
             let span = ty.span();
-            Matcher {
-                kind: MatcherKind::SynType(ty),
-                span,
+            if let Some(parser) = parse_with(input)? {
+                let end_span = parser.span();
+                Matcher {
+                    kind: MatcherKind::ParseWith { ty, parser },
+                    span: span.join(end_span).unwrap_or(span),
+                }
+            } else {
+                Matcher {
+                    kind: MatcherKind::SynType(ty),
+                    span,
+                }
             }
         } else {
             let pattern: Pattern = Pattern::parse(input)?;
@@ -259,7 +507,13 @@ impl Matcher {
         if !input.is_empty() {
             let start_span = cap.span;
             match cap.kind {
-                MatcherKind::SynType(_) | MatcherKind::Enum { .. } => Err(syn::Error::new(
+                MatcherKind::SynType(_)
+                | MatcherKind::ParseWith { .. }
+                | MatcherKind::Enum { .. }
+                | MatcherKind::Alternation(..)
+                | MatcherKind::Record { .. }
+                | MatcherKind::Named { .. }
+                | MatcherKind::Unordered { .. } => Err(syn::Error::new(
                     input.span(),
                     format!("Unexpected '{}'", input),
                 )),
@@ -280,6 +534,579 @@ impl Matcher {
     }
 }
 
+impl NamedItem {
+    /// `#[attr]* vis struct Name as`：必须带有 `struct`，否则 `ident as ...` 仍是字面量模式
+    fn parse_prefix(input: ParseStream) -> syn::Result<Option<Self>> {
+        let fork = input.fork();
+        let declaration = (|| -> syn::Result<_> {
+            let attrs = fork.call(Attribute::parse_outer)?;
+            let visibility: Visibility = fork.parse()?;
+            let struct_token: Token![struct] = fork.parse()?;
+            let ident: Ident = fork.parse()?;
+            Ok((attrs, visibility, struct_token, ident))
+        })();
+        let Ok((attrs, visibility, _struct, ident)) = declaration else {
+            return Ok(None);
+        };
+        // `as "name"` 为语义名称
+        if !fork.peek(Token![as]) || fork.peek2(LitStr) {
+            return Ok(None);
+        }
+        input.advance_to(&fork);
+        Ok(Some(NamedItem {
+            attrs,
+            visibility,
+            _struct,
+            ident,
+            _as: input.parse()?,
+        }))
+    }
+}
+
+impl Matcher {
+    /// 具名嵌套结构 `Name as ...`，`as` 之后的部分与普通的嵌套结构相同
+    fn parse_named(
+        input: ParseStream,
+        item: NamedItem,
+        ctx: &mut ParseContext,
+    ) -> syn::Result<Self> {
+        let start_span = item.ident.span();
+        let matcher = Matcher::parse(input, ctx)?;
+        let MatcherKind::Nested(children) = matcher.kind else {
+            return Err(syn::Error::new(
+                matcher.span,
+                format!("expected a pattern after `{} as`", item.ident),
+            ));
+        };
+        let captures: Vec<_> = children.iter().flat_map(|p| p.collect_captures()).collect();
+        if captures.first().is_some_and(|f| f.is_inline) {
+            return Err(syn::Error::new(
+                matcher.span,
+                "named nested captures need named fields, found an inline capture `#(@: ...)`",
+            ));
+        }
+        Ok(Matcher {
+            kind: MatcherKind::Named { item, children },
+            span: start_span.join(matcher.span).unwrap_or(start_span),
+        })
+    }
+
+    /// 可以是选择的匹配器：在顶层的 `|` 处切分为选择分支，否则与 [`Matcher::parse`] 相同。
+    ///
+    /// 仅用于 `#(A | B)`、`#(x: A | B)` 与 `#(@: A | B)`；其余形式 (e.g. `#(?: a | b)`)
+    /// 中的 `|` 均为字面量
+    pub fn parse_choice(input: ParseStream, ctx: &mut ParseContext) -> syn::Result<Self> {
+        if NamedItem::parse_prefix(&input.fork())?.is_none() {
+            if let Some(alternatives) = split_alternation(input)? {
+                return Matcher::parse_alternation(alternatives, ctx);
+            }
+        }
+        Matcher::parse(input, ctx)
+    }
+
+    /// 匿名选择 `a | b | ...`，各分支依次命名为 `_0`、`_1`……
+    fn parse_alternation(
+        alternatives: Vec<TokenStream>,
+        ctx: &mut ParseContext,
+    ) -> syn::Result<Self> {
+        let mut variants = vec![];
+        for (i, tokens) in alternatives.into_iter().enumerate() {
+            let parser = |input: ParseStream| Matcher::parse(input, ctx);
+            let matcher = parser.parse2(tokens)?;
+            let ident: Type = {
+                let ident = format_ident!("_{}", i);
+                parse_quote!(#ident)
+            };
+            let variant = match &matcher.kind {
+                MatcherKind::SynType(ty) | MatcherKind::Enum { enum_name: ty, .. } => {
+                    EnumVariant::Type {
+                        ident,
+                        ty: ty.clone(),
+                        parser: None,
+                    }
+                }
+                MatcherKind::ParseWith { ty, parser } => EnumVariant::Type {
+                    ident,
+                    ty: ty.clone(),
+                    parser: Some(Box::new(parser.clone())),
+                },
+                MatcherKind::Nested(children) => {
+                    let pattern = Pattern {
+                        kind: PatternKind::Group {
+                            delimiter: Delimiter::None,
+                            children: children.clone(),
+                        },
+                        span: matcher.span,
+                        meta: None,
+                    };
+                    let fields = pattern.collect_captures();
+                    let named = fields.first().map(|f| !f.is_inline).unwrap_or(false);
+                    EnumVariant::Capture {
+                        ident,
+                        named,
+                        fields,
+                        pattern: Box::new(pattern),
+                    }
+                }
+                MatcherKind::Alternation(..) => {
+                    unreachable!("alternatives are parsed without splitting at '|'")
+                }
+                MatcherKind::Unordered { .. } => unreachable!("unordered groups are captures"),
+                MatcherKind::Record { .. } => return Err(syn::Error::new(
+                    matcher.span,
+                    "span(...), tokens(...) and delimiter records cannot be used as an alternative",
+                )),
+                MatcherKind::Named { .. } => {
+                    return Err(syn::Error::new(
+                        matcher.span,
+                        "named nested captures cannot be used as an alternative",
+                    ))
+                }
+            };
+            variants.push((variant, matcher));
+        }
+        let span = variants
+            .first()
+            .map(|(_, m)| m.span)
+            .zip(variants.last().map(|(_, m)| m.span))
+            .map(|(start, end)| start.join(end).unwrap_or(start))
+            .unwrap_or_else(Span::call_site);
+        Ok(Matcher {
+            kind: MatcherKind::Alternation(variants, next_alternation_index()),
+            span,
+        })
+    }
+}
+
+impl Matcher {
+    /// 无序组的成员 `a | b | ...`，每个成员须以字面量（键）开头
+    fn parse_unordered(input: ParseStream, separator: Separator) -> syn::Result<Self> {
+        let span = input.span();
+        let members = match split_alternation(input)? {
+            Some(members) => members,
+            None => vec![input.parse::<TokenStream>()?],
+        };
+        let mut parsed: Vec<Member> = vec![];
+        for tokens in members {
+            let pattern = parse_member.parse2(tokens)?;
+            let member = Member::from_pattern(pattern)?;
+            if parsed
+                .iter()
+                .any(|m| m.key.to_string() == member.key.to_string())
+            {
+                return Err(syn::Error::new(
+                    member.pattern.span,
+                    format!("duplicate key `{}` in the unordered group", member.key),
+                ));
+            }
+            parsed.push(member);
+        }
+        let span = parsed
+            .last()
+            .map(|m| span.join(m.pattern.span).unwrap_or(span))
+            .unwrap_or(span);
+        Ok(Matcher {
+            kind: MatcherKind::Unordered {
+                separator,
+                members: parsed,
+            },
+            span,
+        })
+    }
+}
+
+impl Member {
+    /// 必需成员 `key ...`；可选成员 `#(?: key ...)`，或只记录是否出现的 `#(flag?: #{key})`
+    fn from_pattern(pattern: Pattern) -> syn::Result<Self> {
+        let children = match &pattern.kind {
+            PatternKind::Group {
+                delimiter: Delimiter::None,
+                children,
+            } => children.clone(),
+            _ => vec![pattern.clone()],
+        };
+        let (key, inner, optional) = match children.as_slice() {
+            [Pattern {
+                kind: PatternKind::Literal(key),
+                ..
+            }, ..] => (key.clone(), children, false),
+            [Pattern {
+                kind: PatternKind::Capture(capture),
+                span,
+                ..
+            }] if matches!(capture.quantity, Quantity::Optional) => match &capture.matcher.kind {
+                MatcherKind::Nested(nested) => match nested.first() {
+                    Some(Pattern {
+                        kind: PatternKind::Literal(key),
+                        ..
+                    }) => {
+                        let inner = match capture.binder {
+                            Binder::Anonymous => nested.clone(),
+                            _ => {
+                                let mut capture = capture.clone();
+                                capture.quantity = Quantity::One;
+                                vec![Pattern {
+                                    kind: PatternKind::Capture(capture),
+                                    span: *span,
+                                    meta: None,
+                                }]
+                            }
+                        };
+                        (key.clone(), inner, true)
+                    }
+                    _ => return Err(member_error(pattern.span)),
+                },
+                _ => return Err(member_error(pattern.span)),
+            },
+            _ => return Err(member_error(pattern.span)),
+        };
+        Ok(Member {
+            key,
+            pattern,
+            inner,
+            optional,
+        })
+    }
+}
+
+/// 成员 `#(?: ...)` 与 `#(name?: ...)` 的内容按模式解析，因此键可以是普通标识符 (e.g. `#(skip?: skip)`)
+fn parse_member(input: ParseStream) -> syn::Result<Pattern> {
+    if !(input.peek(Token![#]) && input.peek2(token::Paren)) {
+        return Pattern::parse(input);
+    }
+    let fork = input.fork();
+    let _hash_tag: Token![#] = fork.parse()?;
+    let content;
+    let _paren = parenthesized!(content in fork);
+    let binder = if content.peek(Ident) && content.peek2(Token![?]) {
+        Binder::Named(content.parse()?)
+    } else {
+        Binder::Anonymous
+    };
+    if !(fork.is_empty() && content.peek(Token![?]) && content.peek2(Token![:])) {
+        return Pattern::parse(input);
+    }
+    content.parse::<Token![?]>()?;
+    content.parse::<Token![:]>()?;
+    let mut tokens = vec![];
+    while !content.is_empty() {
+        tokens.push(content.parse::<TokenTree>()?);
+    }
+    let default = match binder {
+        Binder::Named(_) => split_flag_marker(&mut tokens),
+        _ => None,
+    };
+    let inner = Pattern::parse.parse2(tokens.into_iter().collect())?;
+    let span = _hash_tag
+        .span
+        .join(_paren.span.close())
+        .unwrap_or(_hash_tag.span);
+    let children = match inner.kind {
+        PatternKind::Group {
+            delimiter: Delimiter::None,
+            children,
+        } => children,
+        _ => vec![inner],
+    };
+    let matcher = Matcher {
+        kind: MatcherKind::Nested(children),
+        span,
+    };
+    if let Some(default) = &default {
+        check_default(default, &matcher, &Quantity::Optional)?;
+    }
+    input.advance_to(&fork);
+    Ok(Pattern {
+        kind: PatternKind::Capture(Box::new(Capture {
+            _hash_tag,
+            _paren,
+            attrs: Vec::new(),
+            binder,
+            matcher,
+            quantity: Quantity::Optional,
+            default,
+            validator: None,
+            edge: None,
+            span,
+        })),
+        span,
+        meta: None,
+    })
+}
+
+fn member_error(span: Span) -> syn::Error {
+    syn::Error::new(
+        span,
+        "each member of an unordered group must start with a literal key, like 'name = #(name: LitStr)' or '#(skip?: skip)'",
+    )
+}
+
+/// 不以 `name:`、`@:` 或数量限定开头的选择 (e.g. `#(struct | enum)`)
+fn is_alternation(input: ParseStream) -> syn::Result<bool> {
+    if input.peek(Ident) || input.peek(Token![@]) || input.peek(Token![:]) {
+        return Ok(false);
+    }
+    if input.peek(Token![?]) || input.peek(Token![*]) || input.peek(Token![+]) {
+        return Ok(false);
+    }
+    if input.peek(Token![&]) || input.peek(Token![!]) {
+        return Ok(false);
+    }
+    Ok(split_alternation(&input.fork())?.is_some())
+}
+
+/// 匿名选择不保存解析结果，其分支中的捕获无处存放
+fn check_anonymous_alternation(matcher: &Matcher) -> syn::Result<()> {
+    if let MatcherKind::Alternation(variants, _) = &matcher.kind {
+        let has_captures = variants.iter().any(|(v, _)| match v {
+            EnumVariant::Type { .. } => false,
+            EnumVariant::Capture { fields, .. } => !fields.is_empty(),
+        });
+        if has_captures {
+            return Err(syn::Error::new(
+                matcher.span,
+                "captures inside an anonymous alternation are not supported, bind the alternation with a name like '#(kind: ...)'",
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// 前瞻断言只检查不消费，其中的捕获无处存放
+fn check_lookahead(matcher: &Matcher) -> syn::Result<()> {
+    if let MatcherKind::Nested(children) = &matcher.kind {
+        if children.iter().any(|p| !p.collect_captures().is_empty()) {
+            return Err(syn::Error::new(
+                matcher.span,
+                "captures inside a lookahead assertion are not supported",
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// 匹配器本体、缺省值与校验子句
+type Clauses = (TokenStream, Option<Expr>, Option<Expr>);
+
+/// 切分捕获末尾的子句：缺省值 `= expr` 与校验子句 `where f`，均不存在时返回 `None`
+fn split_clauses(input: ParseStream) -> syn::Result<Option<Clauses>> {
+    let fork = input.fork();
+    let mut tokens = vec![];
+    while !fork.is_empty() {
+        tokens.push(fork.parse::<TokenTree>()?);
+    }
+    let validator = split_validator(&mut tokens);
+    let default = split_default(&mut tokens)?;
+    if default.is_none() && validator.is_none() {
+        return Ok(None);
+    }
+    input.advance_to(&fork);
+    Ok(Some((tokens.into_iter().collect(), default, validator)))
+}
+
+/// 在顶层最后一个 `where` 处切分出校验子句 (e.g. `LitInt where |v| check(v)`)
+///
+/// 仅当 `where` 之前非空、之后为闭包时才视为校验子句，否则 `where` 仍作为字面量处理
+/// (e.g. `#(clause?: #(ty: Type) where Self)`)
+fn split_validator(tokens: &mut Vec<TokenTree>) -> Option<Expr> {
+    let pos = tokens
+        .iter()
+        .rposition(|tt| matches!(tt, TokenTree::Ident(ident) if ident == "where"))?;
+    if pos == 0 {
+        return None;
+    }
+    let validator: TokenStream = tokens[pos + 1..].iter().cloned().collect();
+    match syn::parse2::<Expr>(validator) {
+        Ok(validator @ Expr::Closure(_)) => {
+            tokens.truncate(pos);
+            Some(validator)
+        }
+        _ => None,
+    }
+}
+
+/// 在顶层第一个 `=` 处切分出缺省值 (e.g. `Expr = default_expr()`)
+///
+/// 仅当匹配器以标识符开头（即类型）时才视为缺省值，否则 `=` 仍作为字面量处理，
+/// 末尾的 `= bool` 标记除外 (e.g. `#(admin?: ! = bool)`)
+fn split_default(tokens: &mut Vec<TokenTree>) -> syn::Result<Option<Expr>> {
+    if !matches!(tokens.first(), Some(TokenTree::Ident(_))) {
+        return Ok(split_flag_marker(tokens));
+    }
+    let mut joint = false;
+    let mut pos = None;
+    for (i, tt) in tokens.iter().enumerate() {
+        // `==`、`=>`、`<=` 等复合符号中的 `=` 不作为缺省值
+        if let TokenTree::Punct(p) = tt {
+            if !joint && p.as_char() == '=' && p.spacing() == Spacing::Alone {
+                pos = Some(i);
+                break;
+            }
+            joint = p.spacing() == Spacing::Joint;
+        } else {
+            joint = false;
+        }
+    }
+    let Some(pos) = pos else {
+        return Ok(None);
+    };
+    let default: TokenStream = tokens[pos + 1..].iter().cloned().collect();
+    if default.is_empty() {
+        return Err(syn::Error::new(
+            tokens[pos].span(),
+            "expected a default value after '='",
+        ));
+    }
+    let default = syn::parse2::<Expr>(default)?;
+    tokens.truncate(pos);
+    Ok(Some(default))
+}
+
+/// 切分末尾的 `= bool` 标记
+fn split_flag_marker(tokens: &mut Vec<TokenTree>) -> Option<Expr> {
+    let [.., prev, TokenTree::Punct(eq), TokenTree::Ident(ty)] = tokens.as_slice() else {
+        return None;
+    };
+    // `== bool` 等复合符号不作为标记
+    let joint = matches!(prev, TokenTree::Punct(p) if p.spacing() == Spacing::Joint);
+    if joint || eq.as_char() != '=' || eq.spacing() != Spacing::Alone || ty != "bool" {
+        return None;
+    }
+    let marker = Expr::Path(syn::ExprPath {
+        attrs: Vec::new(),
+        qself: None,
+        path: ty.clone().into(),
+    });
+    tokens.truncate(tokens.len() - 2);
+    Some(marker)
+}
+
+/// 缺省值仅用于产生单个字段的捕获，且不能重复；`= bool` 仅用于可以重新构造的可选捕获
+fn check_default(default: &Expr, matcher: &Matcher, quantity: &Quantity) -> syn::Result<()> {
+    if is_flag_marker(default) {
+        if !matches!(quantity, Quantity::Optional) || !matcher.is_flaggable() {
+            return Err(syn::Error::new(
+                default.span(),
+                "'= bool' is only supported on '?' captures of `Token![...]` or literals",
+            ));
+        }
+        return Ok(());
+    }
+    if !matches!(quantity, Quantity::One | Quantity::Optional) {
+        return Err(syn::Error::new(
+            default.span(),
+            "default values are only supported on single or '?' captures",
+        ));
+    }
+    if let MatcherKind::Nested(_) | MatcherKind::Record { .. } = matcher.kind {
+        return Err(syn::Error::new(
+            default.span(),
+            "default values are only supported on type captures",
+        ));
+    }
+    Ok(())
+}
+
+/// 没有 `struct` 的 `ident as ...` 是字面量模式，而不是类型 (e.g. `#(?: value as #(ty: Type))`)
+fn peek_literal_as(input: ParseStream) -> bool {
+    input.peek(Ident) && input.peek2(Token![as]) && !input.peek3(LitStr)
+}
+
+/// 类型之后是否跟随 `with`
+fn peek_with(input: ParseStream) -> bool {
+    input
+        .fork()
+        .parse::<Ident>()
+        .map(|ident| ident == "with")
+        .unwrap_or(false)
+}
+
+/// 自定义解析函数 `with path::to::parse_fn`
+fn parse_with(input: ParseStream) -> syn::Result<Option<Expr>> {
+    if !peek_with(input) {
+        return Ok(None);
+    }
+    let _with: Ident = input.parse()?;
+    if input.is_empty() || input.peek(Token![,]) {
+        return Err(syn::Error::new(
+            _with.span(),
+            "expected a parse function after 'with'",
+        ));
+    }
+    Ok(Some(input.parse()?))
+}
+
+/// 属性转发到生成的字段上，只有具名捕获才有对应的字段；
+/// 字段总会被解析和构造，因此不能用 `#[cfg]` 移除
+fn check_attrs(attrs: &[Attribute], binder: &Binder, matcher: &Matcher) -> syn::Result<()> {
+    let Some(first) = attrs.first() else {
+        return Ok(());
+    };
+    if !matches!(binder, Binder::Named(_)) {
+        return Err(syn::Error::new(
+            first.span(),
+            "attributes are only supported on named captures like '#(#[doc = \"...\"] name: Ident)'",
+        ));
+    }
+    // 记录子模式中的捕获展开到当前层级，`#[cfg]` 无法只作用于记录字段
+    if let (Some(cfg), MatcherKind::Record { .. }) = (cfg_attrs(attrs).first(), &matcher.kind) {
+        return Err(syn::Error::new(
+            cfg.span(),
+            "'#[cfg]' is not supported on span(...), tokens(...) and delimiter records",
+        ));
+    }
+    Ok(())
+}
+
+/// 这些位置的字段以元组形式整体读写，无法单独关闭其中的某个字段
+pub fn check_cfg(fields: &[FieldDef], context: &str) -> syn::Result<()> {
+    match fields.iter().flat_map(FieldDef::cfg_attrs).next() {
+        Some(cfg) => Err(syn::Error::new(
+            cfg.span(),
+            format!("'#[cfg]' is not supported on captures inside {context}"),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// 记录子模式时，子模式中的捕获展开到当前层级，无法随重复次数变化
+fn check_record(matcher: &Matcher, quantity: &Quantity) -> syn::Result<()> {
+    if let MatcherKind::Record { .. } = matcher.kind {
+        if !matches!(quantity, Quantity::One | Quantity::Optional) {
+            return Err(syn::Error::new(
+                matcher.span,
+                "span(...), tokens(...) and delimiter records only support the '?' quantifier",
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// 在顶层的 `|` 处切分选择分支；没有分支，或存在空分支 (e.g. 闭包参数 `| x |`) 时返回 `None`，
+/// 此时 `|` 仍作为字面量处理
+fn split_alternation(input: ParseStream) -> syn::Result<Option<Vec<TokenStream>>> {
+    let fork = input.fork();
+    let mut alternatives = vec![TokenStream::new()];
+    let mut joint = false;
+    while !fork.is_empty() {
+        let tt: TokenTree = fork.parse()?;
+        // `||`、`|=` 等复合符号中的 `|` 不作为分支分隔符
+        let is_bar = !joint
+            && matches!(&tt, TokenTree::Punct(p) if p.as_char() == '|' && p.spacing() == Spacing::Alone);
+        joint = matches!(&tt, TokenTree::Punct(p) if p.spacing() == Spacing::Joint);
+        if is_bar {
+            alternatives.push(TokenStream::new());
+        } else {
+            alternatives.last_mut().unwrap().append(tt);
+        }
+    }
+    if alternatives.len() < 2 || alternatives.iter().any(TokenStream::is_empty) {
+        return Ok(None);
+    }
+    input.advance_to(&fork);
+    Ok(Some(alternatives))
+}
+
 impl Parse for EnumVariant {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
         // 需要支持 Type | TypeName: Type | TypeName: Pattern
@@ -288,13 +1115,14 @@ impl Parse for EnumVariant {
         let fork = input.fork();
         // 尝试解析为Type，如果解析后是','或空，则结束
         if let Ok(ty) = fork.parse::<Type>() {
-            if fork.is_empty() || fork.peek(Token![,]) {
+            if fork.is_empty() || fork.peek(Token![,]) || peek_with(&fork) {
                 input.advance_to(&fork);
+                let parser = parse_with(input)?.map(Box::new);
                 // 如果是Type，那么必须是可简写的模式，可解析为Path
                 let path: Path = parse_quote!(#ty);
                 let ident = path.segments.last().unwrap();
                 let ident = parse_quote!(#ident);
-                return Ok(EnumVariant::Type { ident, ty });
+                return Ok(EnumVariant::Type { ident, ty, parser });
             }
         }
 
@@ -307,9 +1135,10 @@ impl Parse for EnumVariant {
         // 可能是Type或Pattern
         if let Ok(ty) = fork.parse::<Type>() {
             // 必须检查边界：Type 后应该是 ',' 或结束
-            if fork.is_empty() || fork.peek(Token![,]) {
+            if fork.is_empty() || fork.peek(Token![,]) || peek_with(&fork) {
                 input.advance_to(&fork);
-                return Ok(EnumVariant::Type { ident, ty });
+                let parser = parse_with(input)?.map(Box::new);
+                return Ok(EnumVariant::Type { ident, ty, parser });
             }
         }
         {
@@ -348,7 +1177,10 @@ impl EnumVariant {
             EnumVariant::Capture { ident, pattern, .. } => {
                 ident.span().join(pattern.span).unwrap_or(ident.span())
             }
-            EnumVariant::Type { ident, ty } => ident.span().join(ty.span()).unwrap_or(ident.span()),
+            EnumVariant::Type { ident, ty, parser } => {
+                let end_span = parser.as_ref().map(|p| p.span()).unwrap_or(ty.span());
+                ident.span().join(end_span).unwrap_or(ident.span())
+            }
         }
     }
 }
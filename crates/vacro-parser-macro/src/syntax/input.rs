@@ -1,14 +1,18 @@
 use proc_macro2::{Punct, TokenStream, TokenTree};
 use quote::TokenStreamExt;
 use syn::{
-    parenthesized,
-    parse::{discouraged::Speculative, Parse, Parser},
-    Attribute, Local, Stmt, Token,
+    braced, parenthesized,
+    parse::{discouraged::Speculative, Parse, ParseStream, Parser},
+    token, Attribute, Expr, Generics, Ident, Local, Stmt, Token, Visibility,
 };
 
-use crate::ast::{
-    input::{BindInput, DefineInput},
-    node::Pattern,
+use crate::{
+    ast::{
+        capture::{Binder, Capture, Matcher, Quantity},
+        input::{BindInput, DefineInput, MatchArm, MatchInput},
+        node::{Pattern, PatternKind},
+    },
+    syntax::{capture::check_cfg, context::ParseContext},
 };
 
 impl Parse for BindInput {
@@ -39,34 +43,176 @@ impl Parse for BindInput {
         let capture_group;
         let _paren = parenthesized!(capture_group in input);
 
-        let ident = capture_group.parse()?;
-        let _arrow = capture_group.parse()?;
+        // 输入可以是任意表达式，截止到顶层的 `->` 或 `=>`
+        let mut expr = TokenStream::new();
+        while !capture_group.peek(Token![->])
+            && !capture_group.peek(Token![=>])
+            && !capture_group.is_empty()
+        {
+            expr.append(capture_group.parse::<TokenTree>()?);
+        }
+        if expr.is_empty() {
+            return Err(capture_group.error("expected an input expression"));
+        }
+        let input_expr: Expr = syn::parse2(expr)?;
+        let _arrow: Option<Token![->]> = capture_group.parse()?;
+        let fat_arrow = match _arrow {
+            Some(_) => None,
+            None => Some(capture_group.parse()?),
+        };
         let patterns = Pattern::parse(&capture_group)?;
 
         let suffix: TokenStream = input.parse()?;
         Ok(BindInput {
             local,
-            input: ident,
+            input: input_expr,
             _arrow,
+            fat_arrow,
             patterns,
             suffix,
         })
     }
 }
 
+impl Parse for MatchInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        // 与 `match` 相同，输入表达式中的 `{` 不作为结构体字面量
+        let input_expr = Expr::parse_without_eager_brace(input)?;
+        let content;
+        let _brace = braced!(content in input);
+        let mut arms = vec![];
+        while !content.is_empty() {
+            if let Some(MatchArm { patterns: None, .. }) = arms.last() {
+                return Err(content.error("the `_` arm must be the last arm"));
+            }
+            arms.push(content.parse()?);
+        }
+        if arms.is_empty() {
+            return Err(content.error("expected at least one arm"));
+        }
+        Ok(MatchInput {
+            input: input_expr,
+            _brace,
+            arms,
+        })
+    }
+}
+
+impl Parse for MatchArm {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let patterns = if input.peek(Token![_]) && input.peek2(Token![=>]) {
+            input.parse::<Token![_]>()?;
+            None
+        } else {
+            // 模式截止到顶层的 `=>`
+            let mut tokens = TokenStream::new();
+            while !input.peek(Token![=>]) && !input.is_empty() {
+                tokens.append(input.parse::<TokenTree>()?);
+            }
+            if tokens.is_empty() {
+                return Err(input.error("expected a pattern"));
+            }
+            let patterns = Pattern::parse.parse2(tokens)?;
+            // 分支的字段以元组形式传给分支体
+            check_cfg(&patterns.collect_captures(), "`match_tokens!` arms")?;
+            Some(patterns)
+        };
+        let _fat_arrow = input.parse()?;
+        let body: Expr = input.parse()?;
+        // 与 `match` 相同，块表达式之后的逗号可以省略
+        if input.parse::<Option<Token![,]>>()?.is_none()
+            && !input.is_empty()
+            && !matches!(body, Expr::Block(_))
+        {
+            return Err(input.error("expected `,` after the arm body"));
+        }
+        Ok(MatchArm {
+            patterns,
+            _fat_arrow,
+            body,
+        })
+    }
+}
+
 impl Parse for DefineInput {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
         let attrs = input.call(Attribute::parse_outer)?;
         let visibility = input.parse()?;
+        if let Some(enum_token) = input.parse::<Option<Token![enum]>>()? {
+            return Self::parse_enum(input, attrs, visibility, enum_token);
+        }
         let name = input.parse()?;
-        let _colon = input.parse()?;
+        // 泛型参数与 where 子句 (e.g. `Listed<T> where T: Parse:`)
+        let mut generics: Generics = input.parse()?;
+        if input.peek(Token![where]) {
+            generics.where_clause = Some(input.parse()?);
+        }
+        let _colon = Some(input.parse()?);
         let patterns = Pattern::parse(input)?;
         Ok(DefineInput {
             visibility,
+            enum_token: None,
             name,
+            generics,
             _colon,
             patterns,
             attrs,
         })
     }
 }
+
+impl DefineInput {
+    /// `enum Name { ... }` 与嵌套的多态枚举 `#(name: Name { ... })` 共用分支的语法，
+    /// 解析为单个匿名的枚举捕获
+    fn parse_enum(
+        input: ParseStream,
+        attrs: Vec<Attribute>,
+        visibility: Visibility,
+        enum_token: Token![enum],
+    ) -> syn::Result<Self> {
+        let fork = input.fork();
+        let name: Ident = fork.parse()?;
+        if fork.peek(Token![<]) || fork.peek(Token![where]) {
+            return Err(syn::Error::new(
+                fork.span(),
+                "generic parameters are not supported on `define!` enums",
+            ));
+        }
+        if !fork.peek(token::Brace) {
+            return Err(syn::Error::new(fork.span(), "expected `{`"));
+        }
+        let matcher = Matcher::parse(input, &mut ParseContext::default())?;
+        if !input.is_empty() {
+            return Err(syn::Error::new(
+                input.span(),
+                format!("Unexpected '{}'", input),
+            ));
+        }
+        let span = matcher.span;
+        let capture = Capture {
+            _hash_tag: Token![#](span),
+            _paren: token::Paren(span),
+            attrs: Vec::new(),
+            binder: Binder::Anonymous,
+            matcher,
+            quantity: Quantity::One,
+            default: None,
+            validator: None,
+            edge: None,
+            span,
+        };
+        Ok(DefineInput {
+            attrs,
+            visibility,
+            enum_token: Some(enum_token),
+            name,
+            generics: Generics::default(),
+            _colon: None,
+            patterns: Pattern {
+                kind: PatternKind::Capture(Box::new(capture)),
+                span,
+                meta: None,
+            },
+        })
+    }
+}
@@ -0,0 +1,91 @@
+use proc_macro2::{Delimiter, TokenTree};
+use syn::{bracketed, parenthesized, parse::ParseStream, Ident, LitStr, Token};
+
+use crate::ast::meta::SemanticInfo;
+
+impl SemanticInfo {
+    /// 是否为 `#[expect(...)]`（模式中不支持字符串字面量，不会与字面量的 `#[...]` 冲突）
+    pub fn peek_expect(input: ParseStream) -> bool {
+        let Some((_, next)) = input.cursor().punct().filter(|(p, _)| p.as_char() == '#') else {
+            return false;
+        };
+        let Some((inner, _, _)) = next.group(Delimiter::Bracket) else {
+            return false;
+        };
+        let Some((args, _, _)) = inner
+            .ident()
+            .filter(|(ident, _)| ident == "expect")
+            .and_then(|(_, rest)| rest.group(Delimiter::Parenthesis))
+        else {
+            return false;
+        };
+        // 参数中需要有字符串，字面量模式 `#[expect(dead_code)]` 不受影响
+        let mut cursor = args;
+        while let Some((tt, next)) = cursor.token_tree() {
+            if matches!(tt, TokenTree::Literal(_)) {
+                return true;
+            }
+            cursor = next;
+        }
+        false
+    }
+
+    /// `#[expect("描述", example = "...", error = "...")]`
+    pub fn parse_expect(input: ParseStream) -> syn::Result<Self> {
+        input.parse::<Token![#]>()?;
+        let attr;
+        bracketed!(attr in input);
+        attr.parse::<Ident>()?;
+        let args;
+        parenthesized!(args in attr);
+        if !attr.is_empty() {
+            return Err(attr.error("unexpected tokens after `expect(...)`"));
+        }
+
+        let mut info = SemanticInfo::default();
+        if args.peek(LitStr) {
+            info.expectation = Some(args.parse::<LitStr>()?.value());
+            if !args.is_empty() {
+                args.parse::<Token![,]>()?;
+            }
+        }
+        while !args.is_empty() {
+            let key: Ident = args.parse()?;
+            args.parse::<Token![=]>()?;
+            let value = args.parse::<LitStr>()?.value();
+            let slot = match key.to_string().as_str() {
+                "example" => &mut info.example,
+                "error" => &mut info.custom_error,
+                _ => {
+                    return Err(syn::Error::new(
+                        key.span(),
+                        format!("unknown `expect` option `{key}`, expected `example` or `error`"),
+                    ))
+                }
+            };
+            if slot.replace(value).is_some() {
+                return Err(syn::Error::new(
+                    key.span(),
+                    format!("duplicate `expect` option `{key}`"),
+                ));
+            }
+            if !args.is_empty() {
+                args.parse::<Token![,]>()?;
+            }
+        }
+        Ok(info)
+    }
+
+    /// 捕获之后的 `as "名称"`
+    pub fn parse_name(input: ParseStream, meta: Option<Self>) -> syn::Result<Option<Self>> {
+        if !(input.peek(Token![as]) && input.peek2(LitStr)) {
+            return Ok(meta);
+        }
+        input.parse::<Token![as]>()?;
+        let name = input.parse::<LitStr>()?.value();
+        Ok(Some(SemanticInfo {
+            name: Some(name),
+            ..meta.unwrap_or_default()
+        }))
+    }
+}
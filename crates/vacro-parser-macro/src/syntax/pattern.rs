@@ -13,6 +13,7 @@ use crate::{
     ast::{
         capture::Capture,
         keyword::Keyword,
+        meta::SemanticInfo,
         node::{Pattern, PatternKind},
     },
     syntax::{context::ParseContext, keyword::parse_keyword},
@@ -22,10 +23,19 @@ impl Pattern {
     pub fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
         let mut ctx = ParseContext::default();
         let mut pattern_list = vec![];
+        // `#[expect(...)]` 作用于紧随其后的捕获
+        let mut pending_meta = None;
         let start_span = input.span();
         while !input.is_empty() {
             let lookahead = input.lookahead1();
             if lookahead.peek(Token![#]) {
+                if SemanticInfo::peek_expect(input) {
+                    pending_meta = Some(SemanticInfo::parse_expect(input)?);
+                    if !(input.peek(Token![#]) && input.peek2(token::Paren)) {
+                        return Err(input.error("expected a capture after `#[expect(...)]`"));
+                    }
+                    continue;
+                }
                 if input.peek2(token::Brace) {
                     // #{...} 字面量捕获：将大括号内容作为字面量模式
                     let _hash: Token![#] = input.parse()?;
@@ -69,7 +79,7 @@ impl Pattern {
                 let pattern = Pattern {
                     kind: PatternKind::Capture(Box::new(capture)),
                     span,
-                    meta: None,
+                    meta: SemanticInfo::parse_name(input, pending_meta.take())?,
                 };
                 pattern_list.push(pattern);
             } else if lookahead.peek(Ident::peek_any) {
@@ -1 +1,3 @@
+pub mod lint;
 pub mod lookahead;
+pub mod recursion;
@@ -0,0 +1,321 @@
+use proc_macro2::{Delimiter, Span, TokenStream};
+use quote::{quote, quote_spanned, ToTokens};
+use syn::Type;
+
+use crate::ast::{
+    capture::{Capture, EnumVariant, Matcher, MatcherKind, Quantity, RecordKind},
+    first::First,
+    node::{Pattern, PatternKind},
+};
+
+/// 能被 `syn::Lit` 匹配的字面量类型
+const LITS: &[&str] = &[
+    "Lit",
+    "LitStr",
+    "LitByteStr",
+    "LitCStr",
+    "LitByte",
+    "LitChar",
+    "LitInt",
+    "LitFloat",
+    "LitBool",
+];
+
+/// 模式检查发现的问题，报告在对应的捕获上
+pub struct Lint {
+    pub span: Span,
+    pub message: String,
+}
+
+impl Lint {
+    /// 稳定版的过程宏无法直接发出警告，借助使用 `#[deprecated]` 的条目实现
+    pub fn to_warning(&self) -> TokenStream {
+        let message = &self.message;
+        let usage = quote_spanned!(self.span=> grammar_warning);
+        quote! {
+            const _: () = {
+                #[deprecated(note = #message)]
+                #[allow(non_camel_case_types)]
+                struct grammar_warning;
+                let _ = #usage;
+            };
+        }
+    }
+}
+
+/// 对模式进行静态检查：
+/// - 可选或不定次数的捕获与其后的模式可能以相同的 token 开头，前者会贪婪地消费后者需要的输入
+/// - 枚举或选择的分支被之前的分支完全覆盖，永远不会被选中
+/// - 重复捕获的元素可以不消费任何输入，此时重复会提前结束
+pub fn lint(pattern: &Pattern) -> Vec<Lint> {
+    let mut lints = vec![];
+    check_pattern(pattern, &mut lints);
+    lints
+}
+
+fn check_pattern(pattern: &Pattern, lints: &mut Vec<Lint>) {
+    match &pattern.kind {
+        PatternKind::Literal(_) => {}
+        PatternKind::Group { children, .. } => check_sequence(children, lints),
+        PatternKind::Capture(capture) => check_capture(capture, lints),
+    }
+}
+
+fn check_sequence(patterns: &[Pattern], lints: &mut Vec<Lint>) {
+    for pair in patterns.windows(2) {
+        if let PatternKind::Capture(capture) = &pair[0].kind {
+            check_ambiguity(capture, &pair[1], lints);
+        }
+    }
+    for pattern in patterns {
+        check_pattern(pattern, lints);
+    }
+}
+
+fn check_capture(capture: &Capture, lints: &mut Vec<Lint>) {
+    let repeated = matches!(capture.quantity, Quantity::Bounded { .. });
+    if repeated && nullable_matcher(&capture.matcher) {
+        lints.push(Lint {
+            span: capture.span,
+            message: String::from(
+                "the repeated pattern can match without consuming any input, \
+                 which ends the repetition early",
+            ),
+        });
+    }
+    check_matcher(&capture.matcher, lints);
+}
+
+fn check_matcher(matcher: &Matcher, lints: &mut Vec<Lint>) {
+    match &matcher.kind {
+        MatcherKind::SynType(_) | MatcherKind::ParseWith { .. } => {}
+        MatcherKind::Nested(children)
+        | MatcherKind::Named { children, .. }
+        | MatcherKind::Record { children, .. } => check_sequence(children, lints),
+        MatcherKind::Enum { variants, .. } | MatcherKind::Alternation(variants, _) => {
+            check_variants(variants, lints);
+            for (variant, _) in variants {
+                if let EnumVariant::Capture { pattern, .. } = variant {
+                    check_pattern(pattern, lints);
+                }
+            }
+        }
+        MatcherKind::Unordered { members, .. } => {
+            for member in members {
+                check_sequence(&member.inner, lints);
+            }
+        }
+    }
+}
+
+/// 只检查单个 syn 类型的捕获：序列在后续 token 不匹配时会整体回退。
+/// 其后紧跟字面量时由 edge 截断输入，带分隔符的重复在分隔符处结束，均不会产生歧义
+fn check_ambiguity(capture: &Capture, next: &Pattern, lints: &mut Vec<Lint>) {
+    if !matches!(capture.matcher.kind, MatcherKind::SynType(_)) {
+        return;
+    }
+    let greedy = match &capture.quantity {
+        Quantity::Optional => true,
+        Quantity::Bounded {
+            separator: None,
+            min,
+            max,
+        } => *max != Some(*min),
+        _ => false,
+    };
+    if !greedy || matches!(next.kind, PatternKind::Literal(_)) {
+        return;
+    }
+    let (first, next_first) = (First::of_matcher(&capture.matcher), First::of_pattern(next));
+    if matches!(first, First::Unknown) || matches!(next_first, First::Unknown) {
+        return;
+    }
+    if first.overlaps(&next_first) {
+        lints.push(Lint {
+            span: capture.span,
+            message: String::from(
+                "ambiguous grammar: this optional or repeated capture can start with the same \
+                 token as the pattern after it, and will consume the input that pattern needs",
+            ),
+        });
+    }
+}
+
+fn check_variants(variants: &[(EnumVariant, Matcher)], lints: &mut Vec<Lint>) {
+    for (i, (variant, matcher)) in variants.iter().enumerate() {
+        let shadowed_by = variants[..i].iter().find_map(|(earlier, _)| match earlier {
+            EnumVariant::Type {
+                ty, parser: None, ..
+            } if shadows(ty, variant) => Some(ty),
+            _ => None,
+        });
+        if let Some(ty) = shadowed_by {
+            lints.push(Lint {
+                span: matcher.span,
+                message: format!(
+                    "unreachable branch: the earlier branch `{}` already matches any input \
+                     this branch accepts",
+                    ty.to_token_stream()
+                ),
+            });
+        }
+    }
+}
+
+/// 之前的分支 `ty` 是否能匹配分支 `later` 接受的所有输入
+fn shadows(ty: &Type, later: &EnumVariant) -> bool {
+    let Some(name) = type_name(ty) else {
+        return false;
+    };
+    match name.as_str() {
+        "TokenStream" => return true,
+        "TokenTree" => return !nullable_variant(later),
+        _ => {}
+    }
+    let EnumVariant::Type {
+        ty: later_ty,
+        parser: None,
+        ..
+    } = later
+    else {
+        return false;
+    };
+    if ty.to_token_stream().to_string() == later_ty.to_token_stream().to_string() {
+        return true;
+    }
+    let Some(later_name) = type_name(later_ty) else {
+        return false;
+    };
+    let covered = |names: &[&str]| names.contains(&later_name.as_str());
+    match name.as_str() {
+        "Type" => covered(&["Ident", "Path", "TypePath"]),
+        "Expr" => covered(&["Ident", "Path", "ExprPath", "ExprLit"]) || covered(LITS),
+        "Lit" => covered(LITS),
+        _ => false,
+    }
+}
+
+/// 不带泛型参数的类型名 (e.g. `syn::Ident` -> `Ident`)
+fn type_name(ty: &Type) -> Option<String> {
+    match ty {
+        Type::Path(ty) if ty.qself.is_none() => {
+            let segment = ty.path.segments.last()?;
+            segment
+                .arguments
+                .is_none()
+                .then(|| segment.ident.to_string())
+        }
+        Type::Group(ty) => type_name(&ty.elem),
+        Type::Paren(ty) => type_name(&ty.elem),
+        _ => None,
+    }
+}
+
+fn nullable_pattern(pattern: &Pattern) -> bool {
+    match &pattern.kind {
+        PatternKind::Literal(_) => false,
+        PatternKind::Group {
+            delimiter: Delimiter::None,
+            children,
+        } => children.iter().all(nullable_pattern),
+        PatternKind::Group { .. } => false,
+        PatternKind::Capture(capture) => match capture.quantity {
+            Quantity::Optional | Quantity::Lookahead { .. } => true,
+            Quantity::Bounded { min: 0, .. } => true,
+            _ => nullable_matcher(&capture.matcher),
+        },
+    }
+}
+
+fn nullable_matcher(matcher: &Matcher) -> bool {
+    match &matcher.kind {
+        MatcherKind::SynType(ty) => type_name(ty).is_some_and(|name| name == "TokenStream"),
+        MatcherKind::ParseWith { .. } => false,
+        MatcherKind::Record {
+            kind: RecordKind::Delimiter(_),
+            ..
+        } => false,
+        MatcherKind::Nested(children)
+        | MatcherKind::Named { children, .. }
+        | MatcherKind::Record { children, .. } => children.iter().all(nullable_pattern),
+        MatcherKind::Enum { variants, .. } | MatcherKind::Alternation(variants, _) => variants
+            .iter()
+            .any(|(variant, _)| nullable_variant(variant)),
+        MatcherKind::Unordered { members, .. } => members.iter().all(|member| member.optional),
+    }
+}
+
+fn nullable_variant(variant: &EnumVariant) -> bool {
+    match variant {
+        EnumVariant::Type {
+            ty, parser: None, ..
+        } => type_name(ty).is_some_and(|name| name == "TokenStream"),
+        EnumVariant::Type { .. } => false,
+        EnumVariant::Capture { pattern, .. } => nullable_pattern(pattern),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quote::quote;
+    use syn::parse2;
+
+    use crate::ast::input::DefineInput;
+
+    use super::*;
+
+    fn messages(input: TokenStream) -> Vec<String> {
+        let input: DefineInput = parse2(input).unwrap();
+        lint(&input.patterns)
+            .into_iter()
+            .map(|lint| lint.message)
+            .collect()
+    }
+
+    #[test]
+    fn test_lint_ambiguity() {
+        let lints = messages(quote! { Item: #(a?: Ident) #(b: Ident) });
+        assert_eq!(lints.len(), 1);
+        assert!(lints[0].starts_with("ambiguous grammar"));
+        // 关键字不会被 `Ident` 匹配；分隔符与字面量会截断重复
+        assert!(messages(quote! { Item: #(a?: Ident) #(b: Token![fn]) }).is_empty());
+        assert!(messages(quote! { Item: #(a*[,]: Ident) #(b: Ident) }).is_empty());
+        assert!(messages(quote! { Item: #(a*: Ident) , #(b: Ident) }).is_empty());
+        assert!(messages(quote! { Item: #(a{2}: Ident) #(b: Ident) }).is_empty());
+        // 序列中后续的 token 不匹配时整体回退
+        assert!(messages(quote! { Item: #(?: #(a: Ident) ,) #(b: Ident) ; }).is_empty());
+        // 无法确定开头时不报告
+        assert!(messages(quote! { Item: #(a?: Expr) #(b: Ident) }).is_empty());
+    }
+
+    #[test]
+    fn test_lint_unreachable_branch() {
+        let lints = messages(quote! {
+            Item: #(item: Kind {
+                Expr: Expr,
+                Name: Ident,
+                Str: LitStr,
+                Unit: unit,
+            })
+        });
+        assert_eq!(lints.len(), 2);
+        assert!(lints
+            .iter()
+            .all(|m| m.contains("the earlier branch `Expr`")));
+
+        let lints = messages(quote! { Item: #(item: TokenStream | Ident | Type) });
+        assert_eq!(lints.len(), 2);
+        assert_eq!(messages(quote! { Item: #(item: Ident | Ident) }).len(), 1);
+        assert!(messages(quote! { Item: #(item: Ident | Type | Expr) }).is_empty());
+    }
+
+    #[test]
+    fn test_lint_nullable_repeat() {
+        let lints = messages(quote! { Item: #(items*: #(a?: Ident) #(b*: LitInt)) });
+        assert_eq!(lints.len(), 1);
+        assert!(lints[0].starts_with("the repeated pattern"));
+        assert_eq!(messages(quote! { Item: #(items+: TokenStream) }).len(), 1);
+        assert!(messages(quote! { Item: #(items*: #(a?: Ident) #(b: LitInt)) }).is_empty());
+        assert!(messages(quote! { Item: #(items*: #(a?: Ident) [ #(b: Ident) ]) }).is_empty());
+    }
+}
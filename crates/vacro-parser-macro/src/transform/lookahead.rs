@@ -1,4 +1,7 @@
-use crate::ast::node::{Pattern, PatternKind};
+use crate::ast::{
+    capture::Quantity,
+    node::{Pattern, PatternKind},
+};
 
 /// 对模式列表进行“前瞻优化”：
 /// 如果一个捕获组 (Capture) 紧跟着一个字面量 (Literal)，
@@ -41,7 +44,10 @@ pub fn inject_lookahead(patterns: Vec<Pattern>) -> Vec<Pattern> {
             }
 
             // 情况 B: 遇到了新的捕获组 (例如 #(name: Type))
-            PatternKind::Capture(..) => {
+            // 前瞻断言不消费输入，按其他 Token 处理 (情况 C)，避免被截断
+            PatternKind::Capture(capture)
+                if !matches!(capture.quantity, Quantity::Lookahead { .. }) =>
+            {
                 // 如果之前还有一个捕获组没等到字面量 (比如连续两个捕获组)
                 if let Some(prev) = pending_capture {
                     optimized.push(prev); // 前一个只能原样提交
@@ -225,6 +231,46 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_inject_lookahead_skips_assertion() {
+        let ctx = &mut ParseContext::default();
+
+        // 构造序列: [Capture(A), Assertion, Literal(,)]
+        // 期望: 断言不会被注入 ','（否则会截断输入），A 也不会跨过断言被注入
+        let cap_a = parse_capture(quote!(#(a*: Ident)), ctx).unwrap();
+        let assertion = parse_capture(quote!(#(!: Token![;])), ctx).unwrap();
+        let lit_comma = PatternKind::Literal(Keyword::Rust(",".to_string()));
+
+        let patterns = vec![
+            Pattern {
+                kind: PatternKind::Capture(Box::new(cap_a)),
+                span: Span::call_site(),
+                meta: None,
+            },
+            Pattern {
+                kind: PatternKind::Capture(Box::new(assertion)),
+                span: Span::call_site(),
+                meta: None,
+            },
+            Pattern {
+                kind: lit_comma,
+                span: Span::call_site(),
+                meta: None,
+            },
+        ];
+
+        let optimized = inject_lookahead(patterns);
+
+        assert_eq!(optimized.len(), 3);
+        for pattern in &optimized[..2] {
+            if let PatternKind::Capture(c) = &pattern.kind {
+                assert!(c.edge.is_none());
+            } else {
+                panic!("Expected Capture");
+            }
+        }
+    }
+
     #[test]
     fn test_inject_lookahead_interrupted_by_group() {
         let ctx = &mut ParseContext::default();
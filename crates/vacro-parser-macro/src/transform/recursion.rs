@@ -0,0 +1,286 @@
+use proc_macro2::{Group, TokenStream, TokenTree};
+use quote::{quote, ToTokens};
+use syn::{parse_quote, Expr, Generics, Ident, Type};
+
+use crate::ast::{
+    capture::{Capture, EnumVariant, Matcher, MatcherKind, Member, Quantity},
+    node::{Pattern, PatternKind},
+};
+
+/// 处理 `define!` 中对自身的引用：
+/// - 捕获类型中的 `Self` 替换为结构体本身（嵌套的 `*_Item` 中 `Self` 另有所指）
+/// - 直接持有自身的字段（单个、可选、枚举分支）自动包裹为 `Box<Self>`，避免无限大小的类型；
+///   位于 `*` / `+` 等重复捕获之下的引用已经在堆上，保持原样
+pub fn resolve_recursion(pattern: Pattern, name: &Ident, generics: &Generics) -> Pattern {
+    let (_, ty_generics, _) = generics.split_for_impl();
+    let resolver = Resolver {
+        name,
+        self_ty: quote!(#name #ty_generics),
+    };
+    resolver.pattern(pattern, true)
+}
+
+struct Resolver<'a> {
+    name: &'a Ident,
+    self_ty: TokenStream,
+}
+
+impl Resolver<'_> {
+    fn pattern(&self, pattern: Pattern, direct: bool) -> Pattern {
+        let kind = match pattern.kind {
+            PatternKind::Group {
+                delimiter,
+                children,
+            } => PatternKind::Group {
+                delimiter,
+                children: self.patterns(children, direct),
+            },
+            PatternKind::Capture(capture) => {
+                PatternKind::Capture(Box::new(self.capture(*capture, direct)))
+            }
+            literal => literal,
+        };
+        Pattern { kind, ..pattern }
+    }
+
+    fn patterns(&self, patterns: Vec<Pattern>, direct: bool) -> Vec<Pattern> {
+        patterns
+            .into_iter()
+            .map(|p| self.pattern(p, direct))
+            .collect()
+    }
+
+    fn capture(&self, capture: Capture, direct: bool) -> Capture {
+        // 重复捕获的元素保存在 Vec / Punctuated 中，前瞻断言不保存结果
+        let direct = direct && matches!(capture.quantity, Quantity::One | Quantity::Optional);
+        Capture {
+            matcher: self.matcher(capture.matcher, direct),
+            ..capture
+        }
+    }
+
+    fn matcher(&self, matcher: Matcher, direct: bool) -> Matcher {
+        let kind = match matcher.kind {
+            MatcherKind::SynType(ty) => {
+                let (ty, _) = self.boxed(ty, None, direct);
+                MatcherKind::SynType(ty)
+            }
+            MatcherKind::ParseWith { ty, parser } => {
+                let (ty, parser) = self.boxed(ty, Some(parser), direct);
+                MatcherKind::ParseWith {
+                    ty,
+                    parser: parser.expect("parser is kept"),
+                }
+            }
+            MatcherKind::Nested(children) => MatcherKind::Nested(self.patterns(children, direct)),
+            MatcherKind::Named { item, children } => MatcherKind::Named {
+                item,
+                children: self.patterns(children, direct),
+            },
+            MatcherKind::Enum {
+                enum_name,
+                variants,
+            } => MatcherKind::Enum {
+                enum_name,
+                variants: self.variants(variants, direct),
+            },
+            MatcherKind::Alternation(variants, index) => {
+                MatcherKind::Alternation(self.variants(variants, direct), index)
+            }
+            MatcherKind::Record { kind, children } => MatcherKind::Record {
+                kind,
+                children: self.patterns(children, direct),
+            },
+            MatcherKind::Unordered { separator, members } => MatcherKind::Unordered {
+                separator,
+                members: members
+                    .into_iter()
+                    .map(|member| Member {
+                        pattern: self.pattern(member.pattern, direct),
+                        inner: self.patterns(member.inner, direct),
+                        ..member
+                    })
+                    .collect(),
+            },
+        };
+        Matcher { kind, ..matcher }
+    }
+
+    fn variants(
+        &self,
+        variants: Vec<(EnumVariant, Matcher)>,
+        direct: bool,
+    ) -> Vec<(EnumVariant, Matcher)> {
+        variants
+            .into_iter()
+            .map(|(variant, matcher)| {
+                let variant = match variant {
+                    EnumVariant::Type { ident, ty, parser } => {
+                        let (ty, parser) = self.boxed(ty, parser.map(|p| *p), direct);
+                        EnumVariant::Type {
+                            ident,
+                            ty,
+                            parser: parser.map(Box::new),
+                        }
+                    }
+                    EnumVariant::Capture {
+                        ident,
+                        named,
+                        pattern,
+                        ..
+                    } => {
+                        // 分支的字段由模式推导，需要在替换后重新收集
+                        let pattern = self.pattern(*pattern, direct);
+                        EnumVariant::Capture {
+                            ident,
+                            named,
+                            fields: pattern.collect_captures(),
+                            pattern: Box::new(pattern),
+                        }
+                    }
+                };
+                (variant, self.matcher(matcher, direct))
+            })
+            .collect()
+    }
+
+    /// 替换 `Self`，必要时将自身包裹为 `Box`，自定义解析函数的结果一并装箱
+    fn boxed(&self, ty: Type, parser: Option<Expr>, direct: bool) -> (Type, Option<Expr>) {
+        let is_self = self.is_self(&ty);
+        let ty: Type = {
+            let ty = self.replace_self(ty.into_token_stream());
+            parse_quote!(#ty)
+        };
+        let parser = parser.map(|parser| -> Expr {
+            let parser = self.replace_self(parser.into_token_stream());
+            parse_quote!(#parser)
+        });
+        if !(direct && is_self) {
+            return (ty, parser);
+        }
+        let parser = parser.map(|parser| -> Expr {
+            parse_quote! {
+                (|input: ::syn::parse::ParseStream| {
+                    (#parser as fn(::syn::parse::ParseStream) -> ::syn::Result<#ty>)(input)
+                        .map(::std::boxed::Box::new)
+                })
+            }
+        });
+        (parse_quote!(::std::boxed::Box<#ty>), parser)
+    }
+
+    /// 只有单段路径 `Name`、`Name<T>` 或 `Self` 才指向自身，`other::Name` 是另一个类型
+    fn is_self(&self, ty: &Type) -> bool {
+        match ty {
+            Type::Path(ty) if ty.qself.is_none() && ty.path.leading_colon.is_none() => {
+                match ty.path.segments.first() {
+                    Some(seg) if ty.path.segments.len() == 1 => {
+                        seg.ident == *self.name || seg.ident == "Self"
+                    }
+                    _ => false,
+                }
+            }
+            Type::Group(ty) => self.is_self(&ty.elem),
+            Type::Paren(ty) => self.is_self(&ty.elem),
+            _ => false,
+        }
+    }
+
+    fn replace_self(&self, tokens: TokenStream) -> TokenStream {
+        let mut output = TokenStream::new();
+        let mut iter = tokens.into_iter().peekable();
+        while let Some(tt) = iter.next() {
+            match tt {
+                TokenTree::Ident(ident) if ident == "Self" => {
+                    let self_ty = &self.self_ty;
+                    // `Self::f` 需要写成 `<Name<T>>::f`
+                    match iter.peek() {
+                        Some(TokenTree::Punct(p)) if p.as_char() == ':' => {
+                            output.extend(quote!(<#self_ty>))
+                        }
+                        _ => output.extend(self_ty.clone()),
+                    }
+                }
+                TokenTree::Group(group) => {
+                    let mut new = Group::new(group.delimiter(), self.replace_self(group.stream()));
+                    new.set_span(group.span());
+                    output.extend([TokenTree::Group(new)]);
+                }
+                tt => output.extend([tt]),
+            }
+        }
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quote::quote;
+    use syn::parse2;
+
+    use crate::ast::input::DefineInput;
+
+    fn field_types(input: TokenStream) -> Vec<String> {
+        let input: DefineInput = parse2(input).unwrap();
+        let pattern = resolve_recursion(input.patterns, &input.name, &input.generics);
+        pattern
+            .collect_captures()
+            .iter()
+            .map(|f| f.ty.to_token_stream().to_string().replace(' ', ""))
+            .collect()
+    }
+
+    #[test]
+    fn test_resolve_recursion() {
+        let types = field_types(quote! {
+            Tree<T>: #(value: T) #(next?: Self) #(parent: Tree<T>) #(children*[,]: Self) #(raw: Vec<Self>)
+        });
+        assert_eq!(
+            types,
+            [
+                "T",
+                "::std::option::Option<::std::boxed::Box<Tree<T>>>",
+                "::std::boxed::Box<Tree<T>>",
+                "::syn::punctuated::Punctuated<Tree<T>,::syn::Token![,]>",
+                "Vec<Tree<T>>",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolve_recursion_other_path() {
+        // 同名的其他类型不是自身，不装箱
+        let types = field_types(quote! {
+            Tree: #(a: other::Tree) #(b: Self::Assoc) #(c: Tree)
+        });
+        assert_eq!(
+            types,
+            ["other::Tree", "<Tree>::Assoc", "::std::boxed::Box<Tree>",]
+        );
+    }
+
+    #[test]
+    fn test_resolve_recursion_parse_with() {
+        let input: DefineInput =
+            parse2(quote! { Chain: #(next?: Self with Self::parse_next) }).unwrap();
+        let pattern = resolve_recursion(input.patterns, &input.name, &input.generics);
+        let PatternKind::Group { children, .. } = &pattern.kind else {
+            panic!("expected group");
+        };
+        let PatternKind::Capture(capture) = &children[0].kind else {
+            panic!("expected capture");
+        };
+        let MatcherKind::ParseWith { ty, parser } = &capture.matcher.kind else {
+            panic!("expected ParseWith");
+        };
+        assert_eq!(
+            ty.to_token_stream().to_string().replace(' ', ""),
+            "::std::boxed::Box<Chain>"
+        );
+        // 自定义解析函数的结果同样装箱，`Self::` 改写为 `<Chain>::`
+        let parser = parser.to_token_stream().to_string().replace(' ', "");
+        assert!(parser.contains("<Chain>::parse_next"));
+        assert!(parser.contains(".map(::std::boxed::Box::new)"));
+    }
+}
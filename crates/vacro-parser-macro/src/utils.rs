@@ -23,3 +23,15 @@ pub fn resolve_crate_root() -> TokenStream {
 
     quote!(::vacro_parser)
 }
+
+/// 生成代码中使用的 `quote`，经由运行时库转发，使用者无需直接依赖
+pub fn quote_crate() -> TokenStream {
+    let pkg = resolve_crate_root();
+    quote!(#pkg::__private::quote)
+}
+
+/// 生成代码中使用的 `proc_macro2`，经由运行时库转发，使用者无需直接依赖
+pub fn proc_macro2_crate() -> TokenStream {
+    let pkg = resolve_crate_root();
+    quote!(#pkg::__private::proc_macro2)
+}
@@ -1,7 +1,14 @@
 use std::any::type_name;
+use std::fmt::Display;
 use std::marker::PhantomData;
 use std::ops::Deref;
 
+use proc_macro2::{Delimiter, TokenTree};
+
+// 生成的代码经由此处使用，使用者无需直接依赖这两个库
+pub use proc_macro2;
+pub use quote;
+
 // 优先级标签
 pub struct PriorityHigh;
 pub struct PriorityLow;
@@ -58,3 +65,86 @@ impl<T: CustomHelp> HelpImplCustom for HelpQuery<T> {
         <T as CustomHelp>::custom_message()
     }
 }
+
+// 校验子句：确定校验函数的参数类型，错误经由 ValidationError 转换
+pub fn validate<T, E: Display>(
+    value: &T,
+    validator: impl FnOnce(&T) -> Result<(), E>,
+) -> Result<(), E> {
+    validator(value)
+}
+
+// 校验函数返回的错误，通过自动引用优先保留 syn::Error 本身 (包括其 span)
+pub struct ValidationError<E>(pub E);
+
+pub trait ValidationImplSyn {
+    fn message(&self) -> syn::Result<String>;
+}
+
+impl ValidationImplSyn for ValidationError<syn::Error> {
+    fn message(&self) -> syn::Result<String> {
+        Err(self.0.clone())
+    }
+}
+
+pub trait ValidationImplDefault {
+    fn message(&self) -> syn::Result<String>;
+}
+
+impl<E: Display> ValidationImplDefault for &ValidationError<E> {
+    fn message(&self) -> syn::Result<String> {
+        Ok(self.0.to_string())
+    }
+}
+
+// 语义信息：解析失败时用实际遇到的 token 填充错误模板中的 `{found}`
+pub fn semantic_message(template: &str, found: Option<&TokenTree>) -> String {
+    let found = match found {
+        Some(TokenTree::Group(group)) => {
+            let (open, close) = match group.delimiter() {
+                Delimiter::Parenthesis => ("(", ")"),
+                Delimiter::Brace => ("{", "}"),
+                Delimiter::Bracket => ("[", "]"),
+                Delimiter::None => ("", ""),
+            };
+            format!("`{open}...{close}`")
+        }
+        Some(tt) => format!("`{tt}`"),
+        None => String::from("end of input"),
+    };
+    template.replace("{found}", &found)
+}
+
+// 校验失败时附加 help! 中的示例，通过自动引用优先选择自定义实现
+pub struct ExampleQuery<T>(PhantomData<T>);
+impl<T> ExampleQuery<T> {
+    pub fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T> Default for ExampleQuery<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub trait ExampleImplCustom {
+    fn get_example(&self) -> Option<String>;
+}
+
+impl<T: CustomHelp> ExampleImplCustom for ExampleQuery<T> {
+    fn get_example(&self) -> Option<String> {
+        Some(<T as CustomHelp>::custom_message())
+    }
+}
+
+pub trait ExampleImplDefault {
+    fn get_example(&self) -> Option<String>;
+}
+
+impl<T> ExampleImplDefault for &ExampleQuery<T> {
+    fn get_example(&self) -> Option<String> {
+        None
+    }
+}
@@ -15,6 +15,10 @@
 #[doc(hidden)]
 pub mod __private;
 
+pub use vacro_grammar as grammar;
+
 pub use vacro_parser_macro::bind;
 
 pub use vacro_parser_macro::define;
+
+pub use vacro_parser_macro::match_tokens;
@@ -1,6 +1,6 @@
 use quote::quote;
 use std::str::FromStr;
-use syn::{Ident, LitBool, LitInt, Type};
+use syn::{Ident, LitBool, LitInt, Token, Type};
 use vacro_parser::bind;
 
 use proc_macro2::TokenStream;
@@ -197,3 +197,132 @@ fn test_literal() {
         let _ = (input -> #{ #(cow: moww) }).unwrap();
     };
 }
+
+#[test]
+fn test_output_to_tokens() {
+    let input = quote!(fn my_func -> bool);
+    bind! {
+        let res = (input -> fn #(name: Ident) #(?: -> #(ret: Type))).unwrap();
+    }
+    assert_eq!(quote!(#res).to_string(), "fn my_func -> bool");
+}
+
+// 测试匿名选择
+#[test]
+fn test_alternation() {
+    let input = quote!(a => b);
+    bind! {
+        let (lhs, arrow, rhs) =
+            (input -> #(@: Ident) #(@: Token![=>] | Token![->]) #(@: Ident)).unwrap();
+    }
+    assert_eq!(lhs.to_string(), "a");
+    assert_eq!(quote!(#arrow).to_string(), "=>");
+    assert_eq!(rhs.to_string(), "b");
+
+    let input = quote!(enum Foo);
+    bind! {
+        let item = (input -> #(kind: struct | enum) #(name: Ident)).unwrap();
+    }
+    assert_eq!(item.kind, 1);
+    assert_eq!(quote!(#item).to_string(), "enum Foo");
+
+    let input = quote!(union Foo);
+    bind! {
+        let invalid = (input -> #(struct | enum) #(name: Ident));
+    }
+    assert!(invalid.is_err());
+}
+
+// 测试缺省值与存在标记
+#[test]
+fn test_default() {
+    let input = quote!(pub fn run);
+    bind! {
+        let item = (input -> #(is_pub?: Token![pub] = bool) fn #(name: Ident) #(ret?: Type = syn::parse_quote!(()))).unwrap();
+    }
+    assert!(item.is_pub);
+    assert_eq!(item.name.to_string(), "run");
+    assert_eq!(quote!(#item).to_string(), "pub fn run ()");
+}
+
+// 测试无序组
+#[test]
+fn test_unordered() {
+    let input = quote!(b = 2, a = 1);
+    bind! {
+        let args = (input -> #(&[,!]: a = #(a: LitInt) | #(?: b = #(b: LitInt)))).unwrap();
+    }
+    assert_eq!(args.a.base10_digits(), "1");
+    assert_eq!(args.b.as_ref().unwrap().base10_digits(), "2");
+    assert_eq!(quote!(#args).to_string(), "a = 1 , b = 2");
+
+    let input = quote!(a = 1,);
+    bind! {
+        let trailing = (input -> #(&[,!]: a = #(a: LitInt) | #(?: b = #(b: LitInt))));
+    }
+    assert_eq!(
+        trailing.err().unwrap().to_string(),
+        "unexpected end of input, expected one of `a`, `b`"
+    );
+}
+
+// 测试语义信息
+#[test]
+fn test_semantic_info() {
+    let input = quote!(let 1 = 2);
+    bind! {
+        let res = (input -> let #[expect(example = "x")] #(name: Ident) = #(value: LitInt));
+    }
+    assert_eq!(
+        res.err().unwrap().to_string(),
+        "expected name, e.g. `x`, found `1`"
+    );
+}
+
+// 测试任意表达式作为输入
+#[test]
+fn test_expr_input() {
+    let attr: syn::Attribute = syn::parse_quote!(#[route(get, "/index")]);
+    bind!(
+        let (method, path) = (attr.meta.require_list().unwrap().tokens.clone()
+            -> #(@: Ident), #(@: syn::LitStr))
+        .unwrap();
+    );
+    assert_eq!(method.to_string(), "get");
+    assert_eq!(path.value(), "/index");
+
+    let (head, tail) = (quote!(a), quote!(b));
+    bind!(
+        let res = (quote!(#head #tail) -> #(@: Ident) #(@: Ident));
+    );
+    assert!(res.is_ok());
+}
+
+// 测试在已有的 ParseStream 上只消费前缀
+#[test]
+fn test_parse_stream_input() {
+    struct Route {
+        method: Ident,
+        rest: TokenStream,
+    }
+
+    impl syn::parse::Parse for Route {
+        fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+            bind!(
+                let res = (input => #(method: Ident) :)?;
+            );
+            Ok(Route {
+                method: res.method,
+                rest: input.parse()?,
+            })
+        }
+    }
+
+    let route: Route = syn::parse2(quote!(get: "/index" => index)).unwrap();
+    assert_eq!(route.method.to_string(), "get");
+    assert_eq!(route.rest.to_string(), "\"/index\" => index");
+
+    // 前缀不匹配时的错误照常返回
+    let route = syn::parse2::<Route>(quote!(get "/index"));
+    assert!(route.is_err());
+}
@@ -1,8 +1,11 @@
-use quote::quote;
+use quote::{quote, ToTokens};
 use syn::{
+    ext::IdentExt,
     parse::{Parse, Parser},
-    parse2, Block, Expr, FieldValue, FnArg, Generics, Ident, LitBool, LitInt, Member, PatType,
-    Receiver, ReturnType, Stmt, Token, Type,
+    parse2,
+    punctuated::Punctuated,
+    Attribute, Block, Expr, FieldValue, FnArg, Generics, Ident, LitBool, LitInt, LitStr, Member,
+    PatType, Receiver, ReturnType, Stmt, Token, Type,
 };
 use vacro_parser::define;
 
@@ -434,3 +437,1292 @@ fn test_literal() {
     };
     let _ = Password::parse.parse2(input).expect_err("that's wrong");
 }
+
+// ToTokens: 解析结果应当能够按模式顺序还原
+fn assert_round_trip<T: Parse + ToTokens>(input: proc_macro2::TokenStream) {
+    let parsed = T::parse.parse2(input.clone()).unwrap();
+    assert_eq!(quote!(#parsed).to_string(), input.to_string());
+}
+
+#[test]
+fn test_to_tokens_round_trip() {
+    assert_round_trip::<Simple>(quote!(kw hello));
+    assert_round_trip::<FuncSig>(quote!(fn my_func (a, b, c) -> bool));
+    assert_round_trip::<FuncSig>(quote!(fn run()));
+    assert_round_trip::<PolyWrapper>(quote!(start 123 end));
+    assert_round_trip::<MyConfig>(quote!({ a: true, b: false, }));
+    assert_round_trip::<Mixed>(quote!({ x y z }));
+    assert_round_trip::<DeviceCofig>(quote! {
+        DeviceA {
+            transport: LocalSocket,
+            async get_name(&self) -> String {
+                self.name
+            },
+            pub name: String = "device-a".to_string()
+        }
+    });
+    assert_round_trip::<TransportInput>(quote! {
+        TransportA<Adapter>(name: String) {
+            name,
+            version: i32,
+            description: String = "transport-a"
+        }
+    });
+    assert_round_trip::<Password>(quote!(world is mine!));
+    assert_round_trip::<FatArrow>(quote!(a => 1));
+}
+
+// 匿名的 `Token![...]` 不含值，可以重新构造
+define!(FatArrow: #(name: Ident) #(Token![=>]) #(value: LitInt));
+
+/// 通过自动引用判断类型是否实现了 `ToTokens`
+struct Probe<T>(std::marker::PhantomData<T>);
+trait Printable {
+    fn printable(&self) -> bool {
+        true
+    }
+}
+impl<T: ToTokens> Printable for Probe<T> {}
+trait NotPrintable {
+    fn printable(&self) -> bool {
+        false
+    }
+}
+impl<T> NotPrintable for &Probe<T> {}
+macro_rules! is_printable {
+    ($ty:ty) => {{
+        let probe = &Probe::<$ty>(std::marker::PhantomData);
+        probe.printable()
+    }};
+}
+
+#[test]
+fn test_to_tokens_skipped() {
+    // `EventBody::Unamed` 中的匿名捕获 `#(Type)` 不保存解析结果，无法还原
+    assert!(!is_printable!(Device));
+    assert!(!is_printable!(EventBody));
+    assert!(is_printable!(FatArrow));
+}
+
+#[test]
+fn test_to_tokens_modified() {
+    let mut method: Method = parse2(quote!(get_name(&self) -> String { self.name })).unwrap();
+    method.asyncness = Some(Default::default());
+    method.name = Ident::new("fetch_name", proc_macro2::Span::call_site());
+    assert_eq!(
+        quote!(#method).to_string(),
+        quote!(async fetch_name(&self) -> String { self.name }).to_string()
+    );
+}
+
+// 无分隔符的重复捕获
+define!(StmtBlock: {
+    #(stmts*: Stmt)
+});
+
+define!(WordsThenNumbers:
+    #(words*: Ident) ; #(numbers*: LitInt)
+);
+
+define!(IdentsThenNumbers:
+    #(idents*: Ident) #(numbers*: LitInt)
+);
+
+define!(Items:
+    #(items*: syn::Item)
+);
+
+#[test]
+fn test_repeat_without_separator() {
+    let res: StmtBlock = parse2(quote!({
+        let a = 1;
+        a += 1;
+    }))
+    .unwrap();
+    assert_eq!(res.stmts.len(), 2);
+    let res: StmtBlock = parse2(quote!({})).unwrap();
+    assert!(res.stmts.is_empty());
+
+    // 在后续字面量前停止
+    let res: WordsThenNumbers = parse2(quote!(a b c; 1 2)).unwrap();
+    assert_eq!(res.words.len(), 3);
+    assert_eq!(res.numbers.len(), 2);
+    let res: WordsThenNumbers = parse2(quote!(; 1)).unwrap();
+    assert!(res.words.is_empty());
+
+    // 元素在未消费 token 时失败，则结束重复
+    let res: IdentsThenNumbers = parse2(quote!(a b 1 2 3)).unwrap();
+    assert_eq!(res.idents.len(), 2);
+    assert_eq!(res.numbers.len(), 3);
+
+    assert_round_trip::<IdentsThenNumbers>(quote!(a b 1 2 3));
+}
+
+#[test]
+fn test_repeat_without_separator_error() {
+    let res: Items = parse2(quote!(
+        fn a() {}
+        struct S;
+    ))
+    .unwrap();
+    assert_eq!(res.items.len(), 2);
+
+    // 元素已消费 token 后失败，应当报告元素自身的错误
+    let Err(err) = syn::parse_str::<Items>("fn a() {} struct S { x }") else {
+        panic!("incomplete item should fail");
+    };
+    assert_eq!(err.to_string(), "expected `:`");
+    // 指向结构体内部缺失 `:` 的位置，而非重复之后剩余的 `struct`
+    let start = err.span().start();
+    assert_eq!((start.line, start.column), (1, 23));
+}
+
+// 有界重复：+、{n}、{n,m}
+define!(Variants:
+    enum #(name: Ident) { #(variants+[,]: Ident) }
+);
+
+define!(Point:
+    ( #(coords{3}[,]: LitInt) )
+);
+
+define!(Call:
+    #(func: Ident) ( #(args{1,4}[,]: Expr) )
+);
+
+define!(Words:
+    #(words+: Ident)
+);
+
+#[test]
+fn test_bounded_repeat() {
+    let res: Variants = parse2(quote!(
+        enum E {
+            A,
+            B,
+        }
+    ))
+    .unwrap();
+    assert_eq!(res.variants.len(), 2);
+    let Err(err) = parse2::<Variants>(quote!(
+        enum E {}
+    )) else {
+        panic!("empty variants should fail");
+    };
+    assert_eq!(err.to_string(), "expected at least 1 element, found 0");
+
+    let res: Point = parse2(quote!((1, 2, 3))).unwrap();
+    assert_eq!(res.coords.len(), 3);
+    let Err(err) = parse2::<Point>(quote!((1, 2))) else {
+        panic!("two coordinates should fail");
+    };
+    assert_eq!(err.to_string(), "expected exactly 3 elements, found 2");
+    let Err(err) = parse2::<Point>(quote!((1, 2, 3, 4))) else {
+        panic!("four coordinates should fail");
+    };
+    assert_eq!(err.to_string(), "expected exactly 3 elements, found more");
+
+    let res: Call = parse2(quote!(f(a, b + 1))).unwrap();
+    assert_eq!(res.args.len(), 2);
+    let Err(err) = parse2::<Call>(quote!(f())) else {
+        panic!("no arguments should fail");
+    };
+    assert_eq!(err.to_string(), "expected at least 1 element, found 0");
+    let Err(err) = parse2::<Call>(quote!(f(a, b, c, d, e))) else {
+        panic!("five arguments should fail");
+    };
+    assert_eq!(err.to_string(), "expected at most 4 elements");
+
+    let res: Words = parse2(quote!(a b c)).unwrap();
+    assert_eq!(res.words.len(), 3);
+    assert!(parse2::<Words>(quote!()).is_err());
+
+    assert_round_trip::<Call>(quote!(f(a, b + 1,)));
+}
+
+// 末尾分隔符策略：[,] 可选、[,!] 禁止、[;+] 必须
+define!(StrictList:
+    [ #(items*[,!]: Ident) ]
+);
+
+define!(Statements:
+    { #(stmts*[;+]: Expr) }
+);
+
+define!(ListThenString:
+    #(names*[,!]: Ident) #(label: syn::LitStr)
+);
+
+#[test]
+fn test_trailing_separator() {
+    let res: StrictList = parse2(quote!([a, b, c])).unwrap();
+    assert_eq!(res.items.len(), 3);
+    assert!(!res.items.trailing_punct());
+    let res: StrictList = parse2(quote!([])).unwrap();
+    assert!(res.items.is_empty());
+    assert!(parse2::<StrictList>(quote!([a, b,])).is_err());
+
+    let res: Statements = parse2(quote!({
+        a;
+        b + 1;
+    }))
+    .unwrap();
+    assert_eq!(res.stmts.len(), 2);
+    assert!(res.stmts.trailing_punct());
+    let res: Statements = parse2(quote!({})).unwrap();
+    assert!(res.stmts.is_empty());
+    let Err(err) = parse2::<Statements>(quote!({
+        a;
+        b
+    })) else {
+        panic!("missing terminator should fail");
+    };
+    assert_eq!(err.to_string(), "expected `;`");
+
+    let res: FuncSig = parse2(quote!(fn f(a, b,))).unwrap();
+    assert!(res.args.trailing_punct());
+    // 默认 [,] 与 parse_terminated 一致：缺少分隔符时报告期望的分隔符
+    let Err(err) = parse2::<FuncSig>(quote!(fn f(a b))) else {
+        panic!("missing separator should fail");
+    };
+    assert_eq!(err.to_string(), "expected `,`");
+
+    assert_round_trip::<Statements>(quote!({
+        a;
+        b + 1;
+    }));
+}
+
+#[test]
+fn test_repeat_in_middle() {
+    let res: ListThenString = parse2(quote!(a, b "label")).unwrap();
+    assert_eq!(res.names.len(), 2);
+    assert_eq!(res.label.value(), "label");
+
+    assert!(parse2::<ListThenString>(quote!(a, b, "label")).is_err());
+
+    let res: ListThenString = parse2(quote!("label")).unwrap();
+    assert!(res.names.is_empty());
+}
+
+// 匿名选择：a | b
+define!(ItemKind:
+    #(kind: struct | enum) #(name: Ident)
+);
+
+define!(Arrow:
+    #(lhs: Ident) #(arrow: Token![=>] | Token![->]) #(rhs: Ident)
+);
+
+define!(Setting:
+    #(field: #(name: Ident) = #(value: LitInt) | #(flag: Ident))
+);
+
+define!(Visibility:
+    #(pub | priv) #(name: Ident)
+);
+
+define!(Modifiers:
+    #(mods*[,]: #(@: const | static | mut))
+);
+
+// 可选、重复等形式中的 `|` 是字面量
+define!(Piped:
+    #(?: #(lhs: Ident) | #(rhs: Ident))
+);
+
+#[test]
+fn test_alternation() {
+    let res: ItemKind = parse2(quote!(struct Foo)).unwrap();
+    assert_eq!(res.kind, 0);
+    let res: ItemKind = parse2(quote!(enum Foo)).unwrap();
+    assert_eq!(res.kind, 1);
+    assert_eq!(res.name.to_string(), "Foo");
+    let Err(err) = parse2::<ItemKind>(quote!(union Foo)) else {
+        panic!("union should fail");
+    };
+    assert_eq!(
+        err.to_string(),
+        "Expected one of: `struct`, `enum`, get: union Foo"
+    );
+
+    let res: Arrow = parse2(quote!(a -> b)).unwrap();
+    assert!(matches!(
+        res.arrow,
+        __private_scope_for_Arrow::arrow_Item::_1(_)
+    ));
+    assert_round_trip::<Arrow>(quote!(a => b));
+
+    let res: Setting = parse2(quote!(size = 3)).unwrap();
+    let Setting {
+        field: __private_scope_for_Setting::field_Item::_0 { name, value },
+    } = &res
+    else {
+        panic!("expected the first alternative");
+    };
+    assert_eq!(name.to_string(), "size");
+    assert_eq!(value.base10_parse::<u8>().unwrap(), 3);
+    let res: Setting = parse2(quote!(verbose)).unwrap();
+    assert!(matches!(
+        res.field,
+        __private_scope_for_Setting::field_Item::_1 { .. }
+    ));
+
+    assert!(parse2::<Visibility>(quote!(pub x)).is_ok());
+    assert!(parse2::<Visibility>(quote!(priv x)).is_ok());
+    assert!(parse2::<Visibility>(quote!(crate x)).is_err());
+
+    let res: Modifiers = parse2(quote!(mut, const, static)).unwrap();
+    assert_eq!(res.mods.iter().map(|m| m.0).collect::<Vec<_>>(), [2, 0, 1]);
+
+    assert_round_trip::<ItemKind>(quote!(enum Foo));
+    assert_round_trip::<Setting>(quote!(size = 3));
+    assert_round_trip::<Modifiers>(quote!(mut, const, static));
+
+    let res: Piped = parse2(quote!(a | b)).unwrap();
+    assert_eq!(res.lhs.unwrap(), "a");
+    assert_eq!(res.rhs.unwrap(), "b");
+    assert!(parse2::<Piped>(quote!(a)).is_err());
+    assert_round_trip::<Piped>(quote!(a | b));
+}
+
+// 前瞻断言：#(&: ...) 与 #(!: ...)
+define!(UntilSemi:
+    #(head*: #(!: Token![;]) #(@: proc_macro2::TokenTree)) #(rest: proc_macro2::TokenStream)
+);
+
+define!(StartsWithIdent:
+    #(&: Ident) #(first: proc_macro2::TokenTree)
+);
+
+define!(NotWhere:
+    #(!: where) #(name: Ident)
+);
+
+define!(AttrArg:
+    #(arg: AttrArgKind {
+        Path: #(!: #(Ident) =) #(path: syn::Path),
+        NameValue: #(name: Ident) = #(value: Expr),
+    })
+);
+
+#[test]
+fn test_lookahead() {
+    let res: UntilSemi = parse2(quote!(a b; c)).unwrap();
+    assert_eq!(res.head.len(), 2);
+    assert_eq!(res.rest.to_string(), "; c");
+
+    let res: StartsWithIdent = parse2(quote!(a)).unwrap();
+    assert_eq!(res.first.to_string(), "a");
+    let Err(err) = parse2::<StartsWithIdent>(quote!(1)) else {
+        panic!("literal should fail");
+    };
+    assert_eq!(err.to_string(), "expected identifier");
+
+    let res: NotWhere = parse2(quote!(foo)).unwrap();
+    assert_eq!(res.name.to_string(), "foo");
+    let Err(err) = parse2::<NotWhere>(quote!(where)) else {
+        panic!("where should fail");
+    };
+    assert_eq!(err.to_string(), "unexpected `where`");
+
+    let res: AttrArg = parse2(quote!(foo)).unwrap();
+    assert!(matches!(res.arg, AttrArgKind::Path { .. }));
+    let res: AttrArg = parse2(quote!(foo = 1)).unwrap();
+    assert!(matches!(res.arg, AttrArgKind::NameValue { .. }));
+
+    assert_round_trip::<NotWhere>(quote!(foo));
+    assert_round_trip::<AttrArg>(quote!(foo = 1));
+}
+
+// 记录子模式：span(...)、tokens(...) 与 Paren(...)/Brace(...)/Bracket(...)
+define!(SpannedSig:
+    fn #(name: Ident) #(ret_span?: span(-> #(ret: Type)))
+);
+
+define!(RawAttr:
+    #(raw: tokens(#(path: syn::Path) #(?: = #(value: Expr))))
+);
+
+define!(ParenCall:
+    #(func: Ident) #(paren: Paren(#(args*[,]: Expr)))
+);
+
+define!(MaybeBody:
+    #(name: Ident) #(brace?: Brace(#(stmts*: Stmt)))
+);
+
+#[test]
+fn test_record() {
+    let res: SpannedSig = parse2(quote!(fn run -> bool)).unwrap();
+    assert!(res.ret_span.is_some());
+    assert_eq!(res.ret.to_token_stream().to_string(), "bool");
+    let res: SpannedSig = parse2(quote!(fn run)).unwrap();
+    assert!(res.ret_span.is_none());
+    assert!(res.ret.is_none());
+
+    let res: RawAttr = parse2(quote!(my::path = 1 + 2)).unwrap();
+    assert_eq!(res.raw.to_string(), "my :: path = 1 + 2");
+    assert_eq!(res.value.to_token_stream().to_string(), "1 + 2");
+
+    let res: ParenCall = parse2(quote!(call(a, b))).unwrap();
+    assert_eq!(res.args.len(), 2);
+    let _: syn::token::Paren = res.paren;
+    assert!(parse2::<ParenCall>(quote!(call[a, b])).is_err());
+
+    let res: MaybeBody = parse2(quote!(run { let a = 1; })).unwrap();
+    assert!(res.brace.is_some());
+    assert_eq!(res.stmts.unwrap().len(), 1);
+    let res: MaybeBody = parse2(quote!(run)).unwrap();
+    assert!(res.brace.is_none());
+
+    assert_round_trip::<SpannedSig>(quote!(fn run -> bool));
+    assert_round_trip::<SpannedSig>(quote!(fn run));
+    assert_round_trip::<RawAttr>(quote!(my::path = 1));
+    assert_round_trip::<ParenCall>(quote!(call(a, b)));
+    assert_round_trip::<MaybeBody>(quote!(run { let a = 1; }));
+    assert_round_trip::<MaybeBody>(quote!(run));
+}
+
+// 自定义解析函数：#(x: T with path::to::parse_fn)
+define!(AttrStruct:
+    #(attrs: Vec<Attribute> with Attribute::parse_outer) struct #(name: Ident)
+);
+
+define!(ModPath:
+    use #(path: syn::Path with syn::Path::parse_mod_style)
+);
+
+define!(NonEmptyArgs:
+    #(args: Punctuated<Expr, Token![,]> with Punctuated::parse_separated_nonempty)
+);
+
+define!(AnyWords:
+    #(words*[,]: Ident with Ident::parse_any)
+);
+
+define!(AnyToken:
+    #(token: AnyTokenKind {
+        Word: Ident with Ident::parse_any,
+        Number: LitInt,
+    })
+);
+
+define!(WordOrNumber:
+    #(Ident with Ident::parse_any) #(value: Ident with Ident::parse_any | LitInt)
+);
+
+#[test]
+fn test_parse_with() {
+    let res: AttrStruct = parse2(quote!(#[derive(Debug)] #[doc = "a"] struct Foo)).unwrap();
+    assert_eq!(res.attrs.len(), 2);
+    assert_eq!(res.name.to_string(), "Foo");
+
+    let res: ModPath = parse2(quote!(use a::b::c)).unwrap();
+    assert_eq!(res.path.segments.len(), 3);
+    assert!(parse2::<ModPath>(quote!(use a::<T>)).is_err());
+
+    let res: NonEmptyArgs = parse2(quote!(a, b + 1)).unwrap();
+    assert_eq!(res.args.len(), 2);
+    assert!(parse2::<NonEmptyArgs>(quote!()).is_err());
+
+    let res: AnyWords = parse2(quote!(struct, fn, self)).unwrap();
+    assert_eq!(res.words.len(), 3);
+    assert_eq!(res.words[0].to_string(), "struct");
+
+    let res: AnyToken = parse2(quote!(match)).unwrap();
+    assert!(matches!(res.token, AnyTokenKind::Word(_)));
+    let res: AnyToken = parse2(quote!(1)).unwrap();
+    assert!(matches!(res.token, AnyTokenKind::Number(_)));
+
+    let res: WordOrNumber = parse2(quote!(type 1)).unwrap();
+    assert!(matches!(
+        res.value,
+        __private_scope_for_WordOrNumber::value_Item::_1(_)
+    ));
+
+    assert_round_trip::<AttrStruct>(quote!(#[derive(Debug)] struct Foo));
+    assert_round_trip::<ModPath>(quote!(use a::b));
+    assert_round_trip::<NonEmptyArgs>(quote!(a, b));
+    assert_round_trip::<AnyWords>(quote!(struct, fn));
+    assert_round_trip::<AnyToken>(quote!(match));
+}
+
+// 校验子句：#(x: T where |v| validator(v))
+fn check_power_of_two(v: &LitInt) -> Result<(), String> {
+    let n: u64 = v.base10_parse().map_err(|e| e.to_string())?;
+    if n.is_power_of_two() {
+        Ok(())
+    } else {
+        Err(format!("{} is not a power of two", n))
+    }
+}
+
+fn check_unique(keys: &Punctuated<Ident, Token![,]>) -> Result<(), syn::Error> {
+    for (i, key) in keys.iter().enumerate() {
+        if let Some(duplicate) = keys.iter().skip(i + 1).find(|k| *k == key) {
+            return Err(syn::Error::new_spanned(
+                duplicate,
+                format!("duplicate key `{}`", key),
+            ));
+        }
+    }
+    Ok(())
+}
+
+vacro_report::help!(Size: LitInt {
+    error: "expected an integer",
+    help: "use a power of two",
+    example: 64
+});
+
+define!(Align:
+    align = #(size: LitInt where |v| check_power_of_two(v))
+);
+
+define!(SnakeName:
+    #(name: Ident where |v| {
+        if v.to_string().chars().any(|c| c.is_uppercase()) {
+            Err("expected a snake_case name")
+        } else {
+            Ok(())
+        }
+    }) ;
+);
+
+define!(UniqueKeys:
+    #(keys*[,]: Ident where |v| check_unique(v))
+);
+
+define!(HelpedAlign:
+    #(size: Size where |v| check_power_of_two(v))
+);
+
+#[test]
+fn test_validator() {
+    let res: Align = parse2(quote!(align = 64)).unwrap();
+    assert_eq!(res.size.base10_digits(), "64");
+    let Err(err) = parse2::<Align>(quote!(align = 12)) else {
+        panic!("12 is not a power of two");
+    };
+    assert_eq!(err.to_string(), "12 is not a power of two");
+
+    assert!(parse2::<SnakeName>(quote!(my_name;)).is_ok());
+    let Err(err) = parse2::<SnakeName>(quote!(MyName;)) else {
+        panic!("MyName is not snake_case");
+    };
+    assert_eq!(err.to_string(), "expected a snake_case name");
+
+    let res: UniqueKeys = parse2(quote!(a, b, c)).unwrap();
+    assert_eq!(res.keys.len(), 3);
+    let Err(err) = parse2::<UniqueKeys>(quote!(a, b, a)) else {
+        panic!("duplicate keys");
+    };
+    assert_eq!(err.to_string(), "duplicate key `a`");
+    // 校验函数返回的 syn::Error 保留其自身的 span，指向重复的键
+    let Err(err) = syn::parse_str::<UniqueKeys>("a, b, a") else {
+        panic!("duplicate keys");
+    };
+    let start = err.span().start();
+    assert_eq!((start.line, start.column), (1, 6));
+
+    let Err(err) = parse2::<HelpedAlign>(quote!(3)) else {
+        panic!("3 is not a power of two");
+    };
+    assert_eq!(err.to_string(), "3 is not a power of two\nexample: 64");
+}
+
+fn default_value() -> Expr {
+    syn::parse_quote!(0)
+}
+
+define!(Variable:
+    #(name: Ident)
+    #(?: : #(ty: Type = syn::parse_quote!(i32)))
+    #(?: = #(value: Expr = default_value()))
+    #(mutable?: Token![mut] = bool)
+);
+
+#[test]
+fn test_default() {
+    let res: Variable = parse2(quote!(count: u8 = 8 mut)).unwrap();
+    assert_eq!(res.name, "count");
+    assert_eq!(res.ty.to_token_stream().to_string(), "u8");
+    assert_eq!(res.value.to_token_stream().to_string(), "8");
+    assert!(res.mutable);
+
+    let res: Variable = parse2(quote!(count)).unwrap();
+    assert_eq!(res.ty.to_token_stream().to_string(), "i32");
+    assert_eq!(res.value.to_token_stream().to_string(), "0");
+    assert!(!res.mutable);
+
+    // 缺省值被补全后再打印，结果包含完整的形式
+    assert_eq!(
+        res.to_token_stream().to_string(),
+        quote!(count: i32 = 0).to_string()
+    );
+    assert_round_trip::<Variable>(quote!(count: u8 = 8 mut));
+}
+
+// 无序组：键可以按任意顺序出现，每个至多一次
+define!(FieldArgs:
+    #(&[,]:
+        name = #(name: LitStr)
+        | #(?: rename_all = #(rename_all: LitStr))
+        | #(?: default = #(fallback: Expr = default_value()))
+        | #(skip?: skip = bool)
+    )
+);
+
+#[test]
+fn test_unordered() {
+    let res: FieldArgs = parse2(quote!(skip, rename_all = "camelCase", name = "x")).unwrap();
+    assert_eq!(res.name.value(), "x");
+    assert_eq!(res.rename_all.unwrap().value(), "camelCase");
+    assert_eq!(res.fallback.to_token_stream().to_string(), "0");
+    assert!(res.skip);
+
+    let res: FieldArgs = parse2(quote!(name = "x", default = 1,)).unwrap();
+    assert!(res.rename_all.is_none());
+    assert_eq!(res.fallback.to_token_stream().to_string(), "1");
+    assert!(!res.skip);
+
+    let Err(err) = parse2::<FieldArgs>(quote!(name = "x", skip, name = "y")) else {
+        panic!("duplicate key");
+    };
+    assert_eq!(err.to_string(), "`name` specified twice");
+    let Err(err) = parse2::<FieldArgs>(quote!(skip)) else {
+        panic!("missing key");
+    };
+    assert_eq!(err.to_string(), "missing `name`");
+
+    // 按声明顺序打印
+    assert_eq!(
+        res.to_token_stream().to_string(),
+        quote!(name = "x", default = 1).to_string()
+    );
+    assert_round_trip::<FieldArgs>(quote!(
+        name = "x",
+        rename_all = "snake_case",
+        default = 2,
+        skip
+    ));
+}
+
+// 泛型参数
+define!(Listed<T: Parse>: [ #(items*[,]: T) ]);
+
+define!(Pairs<K, V> where K: Parse, V: Parse:
+    #(pairs*[,!]: #(key: K) => #(value: V))
+    #(?: ; #(tail: K | V))
+);
+
+// 只用于解析而不保存的泛型参数仍保留在结构体上
+define!(Marked<'a, T: Parse>: #(name: Ident) #(T));
+
+#[test]
+fn test_generics() {
+    let res: Listed<Ident> = parse2(quote!([a, b, c])).unwrap();
+    assert_eq!(res.items.len(), 3);
+    let res: Listed<LitInt> = parse2(quote!([1, 2])).unwrap();
+    assert_eq!(res.items[1].base10_parse::<u8>().unwrap(), 2);
+    assert!(parse2::<Listed<LitInt>>(quote!([a])).is_err());
+    assert_round_trip::<Listed<Ident>>(quote!([a, b]));
+
+    let res: Pairs<Ident, LitInt> = parse2(quote!(a => 1, b => 2; 3)).unwrap();
+    assert_eq!(res.pairs.len(), 2);
+    assert_eq!(res.pairs[0].key, "a");
+    assert_eq!(res.pairs[1].value.base10_parse::<u8>().unwrap(), 2);
+    assert!(res.tail.is_some());
+    assert_round_trip::<Pairs<Ident, LitInt>>(quote!(a => 1; b));
+
+    let res: Marked<'static, LitInt> = parse2(quote!(a 1)).unwrap();
+    assert_eq!(res.name, "a");
+    assert!(parse2::<Marked<'static, LitInt>>(quote!(a b)).is_err());
+    assert!(parse2::<Marked<'static, Ident>>(quote!(a b)).is_ok());
+}
+
+// 递归语法：对自身的直接引用自动装箱
+define!(Tree:
+    #(name: Ident)
+    #(?: { #(children*[,]: Self) })
+);
+
+define!(Chain:
+    #(head: Ident)
+    #(?: -> #(next: Self))
+);
+
+fn parse_chain(input: syn::parse::ParseStream) -> syn::Result<Chain> {
+    input.parse()
+}
+
+define!(Term:
+    #(node: Node {
+        Lit: LitInt,
+        Neg: - #(@: Self),
+        Paren: ( #(inner: Term) ),
+        Chained: Self with parse_chain_term,
+    })
+    #(?: + #(rest: Self))
+);
+
+fn parse_chain_term(input: syn::parse::ParseStream) -> syn::Result<Term> {
+    input.parse::<Token![~]>()?;
+    input.parse()
+}
+
+define!(Nest:
+    #(wrapper: #(value: LitInt) | [ #(inner: Nest) ])
+    #(?: ; #(pair: #(left: Self) , #(right: Self with parse_nest)))
+);
+
+fn parse_nest(input: syn::parse::ParseStream) -> syn::Result<Nest> {
+    input.parse()
+}
+
+// 跨结构体的相互递归需要在其中一条边上显式使用 Box
+define!(Outer: [ #(inner?: Box<Inner>) ]);
+define!(Inner: ( #(outer?: Outer) ));
+
+#[test]
+fn test_recursion() {
+    let res: Tree = parse2(quote!(a { b { c, d }, e {} })).unwrap();
+    let children = res.children.unwrap();
+    assert_eq!(children.len(), 2);
+    assert_eq!(children[0].children.as_ref().unwrap()[1].name, "d");
+    assert_round_trip::<Tree>(quote!(a { b { c , d } , e { } }));
+    // 语法描述中的 `Self` 写作语法名
+    assert_eq!(
+        Tree::grammar().to_ebnf(),
+        "Tree ::= Ident ( '{' ( Tree ( ',' Tree )* )? '}' )?"
+    );
+    assert!(!Term::grammar().to_ebnf().contains("Self"));
+
+    let res: Chain = parse2(quote!(a -> b -> c)).unwrap();
+    let next: &Chain = res.next.as_deref().unwrap();
+    assert_eq!(next.next.as_ref().unwrap().head, "c");
+    assert_eq!(parse_chain.parse2(quote!(x)).unwrap().head, "x");
+
+    let res: Term = parse2(quote!(-(1 + ~2) + 3)).unwrap();
+    let Node::Neg(neg) = &res.node else {
+        panic!("expected Neg");
+    };
+    let Node::Paren { inner } = &neg.node else {
+        panic!("expected Paren");
+    };
+    assert!(matches!(
+        &inner.rest.as_ref().unwrap().node,
+        Node::Chained(_)
+    ));
+    // 右递归：`+ 3` 归属于内层的项
+    assert!(res.rest.is_none() && neg.rest.is_some());
+    assert_round_trip::<Term>(quote!(-(1 + 2) + 3));
+
+    let res: Nest = parse2(quote!([[1]]; 2, 3)).unwrap();
+    assert!(matches!(
+        &res.wrapper,
+        __private_scope_for_Nest::wrapper_Item::_1 { .. }
+    ));
+    let pair = res.pair.unwrap();
+    assert!(matches!(
+        &pair.left.wrapper,
+        __private_scope_for_Nest::wrapper_Item::_0 { .. }
+    ));
+
+    let res: Outer = parse2(quote!([([([])])])).unwrap();
+    let inner = res.inner.unwrap().outer.unwrap().inner.unwrap();
+    assert!(inner.outer.unwrap().inner.is_none());
+}
+
+#[test]
+fn test_deep_recursion() {
+    let mut input = quote!(leaf);
+    for i in 0..64u32 {
+        let name = quote::format_ident!("n{}", i);
+        input = quote!(#name { #input });
+    }
+    let mut tree: &Tree = &parse2(input).unwrap();
+    let mut depth = 0;
+    while let Some(child) = tree.children.as_ref().and_then(|c| c.first()) {
+        tree = child;
+        depth += 1;
+    }
+    assert_eq!(depth, 64);
+    assert_eq!(tree.name, "leaf");
+
+    let mut input = quote!(1);
+    for _ in 0..64 {
+        input = quote!(-(#input + 1));
+    }
+    assert_round_trip::<Term>(input);
+}
+
+// 语义信息：更友好的错误信息
+define!(NamedFn:
+    fn
+    #[expect("a snake_case identifier", example = "my_fn")]
+    #(name: Ident) as "function name"
+    ( #(args*[,!]: #(arg: Ident) as "argument") )
+    #[expect(error = "a function body is required, found {found}")]
+    #(body: Block)
+);
+
+#[test]
+fn test_semantic_info() {
+    let res: NamedFn = parse2(quote!(fn my_fn(a, b) {})).unwrap();
+    assert_eq!(res.name, "my_fn");
+    assert_eq!(res.args.len(), 2);
+    assert_round_trip::<NamedFn>(quote!(fn my_fn(a, b) {}));
+
+    let Err(err) = parse2::<NamedFn>(quote!(fn 123() {})) else {
+        panic!("expected an error");
+    };
+    assert_eq!(
+        err.to_string(),
+        "expected function name (a snake_case identifier), e.g. `my_fn`, found `123`"
+    );
+    let Err(err) = parse2::<NamedFn>(quote!(fn)) else {
+        panic!("expected an error");
+    };
+    assert_eq!(
+        err.to_string(),
+        "expected function name (a snake_case identifier), e.g. `my_fn`, found end of input"
+    );
+    let Err(err) = parse2::<NamedFn>(quote!(fn f(a, 1) {})) else {
+        panic!("expected an error");
+    };
+    assert_eq!(err.to_string(), "expected argument, found `1`");
+    let Err(err) = parse2::<NamedFn>(quote!(
+        fn f();
+    )) else {
+        panic!("expected an error");
+    };
+    assert_eq!(err.to_string(), "a function body is required, found `;`");
+    let Err(err) = parse2::<NamedFn>(quote!(fn f() (x))) else {
+        panic!("expected an error");
+    };
+    assert_eq!(
+        err.to_string(),
+        "a function body is required, found `(...)`"
+    );
+}
+
+// 多态枚举的模式分支：报告前进最远的分支的错误
+define!(Decl:
+    #(item: DeclKind {
+        Field: #(name: Ident) : #(ty: Type),
+        Call: call ( #(args*[,]: Expr) ),
+        Unit: #(?: pub) unit,
+    })
+);
+
+#[test]
+fn test_enum_variant_error() {
+    let res: Decl = parse2(quote!(x: u8)).unwrap();
+    assert!(matches!(res.item, DeclKind::Field { .. }));
+
+    // Field 分支解析了 `name` 后失败，前进最远
+    let Err(err) = parse2::<Decl>(quote!(name = u8)) else {
+        panic!("expected an error");
+    };
+    assert_eq!(err.to_string(), "expected `:`");
+    // 在分组内部失败的分支同样计入进度
+    let Err(err) = parse2::<Decl>(quote!(call(a b))) else {
+        panic!("expected an error");
+    };
+    assert_eq!(err.to_string(), "expected `,`");
+
+    // 所有分支都没有前进时，列出各分支的描述
+    let Err(err) = parse2::<Decl>(quote!(1)) else {
+        panic!("expected an error");
+    };
+    assert_eq!(
+        err.to_string(),
+        "Expected one of: `<Ident> : <Type>`, `call ( <Expr>, ... )`, `pub? unit`, get: 1"
+    );
+}
+
+// 多态枚举按开头的 token 分派：开头唯一的分支 peek 成功后直接解析
+define!(Entry:
+    #(item: EntryKind {
+        Func: fn #(name: Ident) ( #(args*[,]: Ident) ),
+        Flag: flag #(name: Ident),
+        Pair: #(key: Ident) = #(value: Expr),
+        Text: LitStr,
+        Block: { #(stmts*: Stmt) },
+        Other: Expr,
+    })
+);
+
+#[test]
+fn test_enum_peek_dispatch() {
+    let res: Entry = parse2(quote!(fn run())).unwrap();
+    assert!(matches!(res.item, EntryKind::Func { .. }));
+    // `flag` 同样能作为 `Ident` 开头，仍需回退尝试 Pair
+    let res: Entry = parse2(quote!(flag verbose)).unwrap();
+    assert!(matches!(res.item, EntryKind::Flag { .. }));
+    let res: Entry = parse2(quote!(flag = 1)).unwrap();
+    assert!(matches!(res.item, EntryKind::Pair { .. }));
+    let res: Entry = parse2(quote!("text")).unwrap();
+    assert!(matches!(res.item, EntryKind::Text(_)));
+    let res: Entry = parse2(quote!({
+        let a = 1;
+    }))
+    .unwrap();
+    assert!(matches!(res.item, EntryKind::Block { .. }));
+    // 开头无法确定的分支按顺序尝试
+    let res: Entry = parse2(quote!(1 + 2)).unwrap();
+    assert!(matches!(res.item, EntryKind::Other(_)));
+
+    // `fn` 只可能是 Func 分支，直接报告该分支的错误
+    let Err(err) = parse2::<Entry>(quote!(fn 1)) else {
+        panic!("expected an error");
+    };
+    assert_eq!(err.to_string(), "expected identifier");
+}
+
+// `Lit`、`LitInt` 能解析负数，`-` 开头的分支不能独占
+define!(Sign: #(v: SignKind { Neg: - #(x: Ident), Num: LitInt }));
+define!(InlineSign: #(v: - #(x: Ident) | #(n: LitInt)));
+
+#[test]
+fn test_enum_peek_negative_literal() {
+    let res: Sign = parse2(quote!(-1)).unwrap();
+    assert!(matches!(&res.v, SignKind::Num(n) if n.base10_digits() == "-1"));
+    let res: Sign = parse2(quote!(-x)).unwrap();
+    assert!(matches!(res.v, SignKind::Neg { .. }));
+
+    let res: InlineSign = parse2(quote!(-1)).unwrap();
+    assert!(matches!(
+        &res.v,
+        __private_scope_for_InlineSign::v_Item::_1 { n } if n.base10_digits() == "-1"
+    ));
+    let res: InlineSign = parse2(quote!(-x)).unwrap();
+    assert!(matches!(
+        res.v,
+        __private_scope_for_InlineSign::v_Item::_0 { .. }
+    ));
+}
+
+// 语法描述：`grammar()` 返回结构化的语法，`Display` 输出单行用法
+define!(Signature:
+    #(vis?: syn::Visibility) fn #(name: Ident) ( #(args*[,]: FnArg) )
+    #(ret?: -> #(ty: Type))
+    #(!: Token![where])
+    #(body: SignatureBody { Block, Semi: Token![;] })
+);
+
+define!(Bounds:
+    #(kind: struct | enum) #(names{1,3}[,]: Ident) [ #(size: LitInt) ]
+);
+
+#[test]
+fn test_grammar() {
+    use std::borrow::Cow;
+    use vacro_parser::grammar::{Delimiter, Grammar, Node, Variant};
+
+    const fn capture(name: Option<&'static str>, ty: &'static str) -> Node {
+        Node::Capture {
+            name: match name {
+                Some(name) => Some(Cow::Borrowed(name)),
+                None => None,
+            },
+            ty: Cow::Borrowed(ty),
+            doc: None,
+        }
+    }
+    // `grammar()` 是 const fn，语法描述借用静态数据而不分配内存
+    const GRAMMAR: Grammar = Signature::grammar();
+    assert!(matches!(GRAMMAR.items, Cow::Borrowed(_)));
+
+    let grammar = Signature::grammar();
+    assert_eq!(grammar.name, "Signature");
+    assert_eq!(
+        grammar.items[..3],
+        [
+            Node::Optional {
+                items: vec![capture(Some("vis"), "syn::Visibility")].into(),
+            },
+            Node::Literal {
+                text: Cow::Borrowed("fn")
+            },
+            capture(Some("name"), "Ident"),
+        ]
+    );
+    assert_eq!(
+        grammar.items[3],
+        Node::Group {
+            delimiter: Delimiter::Parenthesis,
+            items: vec![Node::Repeat {
+                min: 0,
+                max: None,
+                separator: Some(Cow::Borrowed(",")),
+                items: vec![capture(Some("args"), "FnArg")].into(),
+            }]
+            .into(),
+        }
+    );
+    assert_eq!(
+        grammar.items[4],
+        Node::Optional {
+            items: vec![Node::Nested {
+                name: Some(Cow::Borrowed("ret")),
+                doc: None,
+                items: vec![
+                    Node::Literal {
+                        text: Cow::Borrowed("->")
+                    },
+                    capture(Some("ty"), "Type"),
+                ]
+                .into(),
+            }]
+            .into(),
+        }
+    );
+    assert!(matches!(
+        grammar.items[5],
+        Node::Lookahead { negative: true, .. }
+    ));
+    let Node::Choice {
+        name: Some(name),
+        ty: Some(ty),
+        variants,
+    } = &grammar.items[6]
+    else {
+        panic!("expected a choice");
+    };
+    assert_eq!((&**name, &**ty), ("body", "SignatureBody"));
+    assert_eq!(
+        variants[1],
+        Variant {
+            name: Cow::Borrowed("Semi"),
+            items: vec![capture(None, "Token![;]")].into(),
+        }
+    );
+    assert_eq!(
+        grammar.to_string(),
+        "<syn::Visibility>? fn <Ident> ( <FnArg>, ... ) -> <Type>? <SignatureBody>"
+    );
+
+    let grammar = Bounds::grammar();
+    assert!(matches!(
+        grammar.items[1],
+        Node::Repeat {
+            min: 1,
+            max: Some(3),
+            ..
+        }
+    ));
+    assert_eq!(
+        grammar.to_string(),
+        "struct | enum <Ident>, ...{1,3} [ <LitInt> ]"
+    );
+}
+
+#[test]
+fn test_grammar_render() {
+    assert_eq!(
+        Signature::grammar().to_ebnf(),
+        "Signature ::= syn::Visibility? 'fn' Ident '(' ( FnArg ( ',' FnArg )* )? ')' \
+         ( '->' Type )? !'where' SignatureBody\n\
+         SignatureBody ::= Block | ';'"
+    );
+    assert_eq!(
+        Bounds::grammar().to_ebnf(),
+        "Bounds ::= ( 'struct' | 'enum' ) Ident ( ',' Ident ){0,2} '[' LitInt ']'"
+    );
+
+    let svg = Signature::grammar().to_railroad_svg();
+    assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+    assert!(svg.ends_with("</svg>"));
+    // 嵌入 rustdoc 时不能出现空行
+    assert!(!svg.contains("\n\n"));
+    assert!(svg.contains(">syn::Visibility</text>"));
+    assert!(svg.contains(">-&gt;</text>"));
+    assert!(svg.contains(">not 'where'</text>"));
+}
+
+// 顶层枚举：整个输入即为多个分支之一
+define!(
+    #[derive(Debug)]
+    pub enum TopItem {
+        Fn: fn #(name: Ident),
+        Struct: struct #(name: Ident),
+        Ident,
+    }
+);
+
+define!(enum Operand {
+    Paren: ( #(inner: Self) ),
+    Neg: - #(@: Operand),
+    Lit: LitInt,
+});
+
+#[test]
+fn test_define_enum() {
+    let res: TopItem = parse2(quote!(fn run)).unwrap();
+    assert!(matches!(res, TopItem::Fn { name } if name == "run"));
+    let res: TopItem = parse2(quote!(struct Point)).unwrap();
+    assert!(matches!(res, TopItem::Struct { name } if name == "Point"));
+    let res: TopItem = parse2(quote!(other)).unwrap();
+    assert!(matches!(&res, TopItem::Ident(name) if name == "other"));
+    assert!(parse2::<TopItem>(quote!(fn run extra)).is_err());
+    assert_eq!(res.to_token_stream().to_string(), "other");
+
+    // 直接持有自身的分支自动装箱
+    let res: Operand = parse2(quote!((-(1)))).unwrap();
+    let Operand::Paren { inner } = res else {
+        panic!("expected Paren");
+    };
+    let Operand::Neg(inner) = *inner else {
+        panic!("expected Neg");
+    };
+    assert!(matches!(*inner, Operand::Paren { .. }));
+
+    assert_eq!(
+        TopItem::grammar().to_string(),
+        "fn <Ident> | struct <Ident> | <Ident>"
+    );
+    assert_eq!(
+        Operand::grammar().to_ebnf(),
+        "Operand ::= ( '(' Operand ')' | '-' Operand | LitInt )"
+    );
+}
+
+// 具名的嵌套结构体：可在外部命名、实现 trait，并在其他 define! 中复用
+define!(
+    #[derive(Debug, Clone)]
+    pub Roles: roles = [ #(roles*[,]: #[derive(Debug, Clone, PartialEq)] pub struct Role as #(ident: Ident) #(admin?: ! = bool)) ]
+);
+
+define!(Assign: #(first: Role) => #(rest?: Role));
+
+fn role_names(roles: &Roles) -> Vec<String> {
+    roles
+        .roles
+        .iter()
+        .map(|role: &Role| role.ident.to_string())
+        .collect()
+}
+
+#[test]
+fn test_named_nested() {
+    let roles: Roles = parse2(quote!(roles = [alice!, bob])).unwrap();
+    assert_eq!(role_names(&roles), ["alice", "bob"]);
+    assert!(roles.roles[0].admin);
+    assert!(!roles.roles[1].admin);
+    assert_eq!(
+        roles.to_token_stream().to_string(),
+        "roles = [alice ! , bob]"
+    );
+
+    // 实现了常规的 `syn::parse::Parse` 与 `ToTokens`
+    let role: Role = parse2(quote!(carol!)).unwrap();
+    assert_eq!(role.to_token_stream().to_string(), "carol !");
+
+    let assign: Assign = parse2(quote!(alice => bob!)).unwrap();
+    assert_eq!(
+        assign.first,
+        Role {
+            admin: false,
+            ..roles.roles[0].clone()
+        }
+    );
+    assert!(assign.rest.unwrap().admin);
+
+    // 未指定 derive 时沿用外层的 derive
+    define!(#[derive(Debug)] Wrapper: #(inner: struct Inner as < #(ty: Type) >));
+    let wrapper: Wrapper = parse2(quote!(<u8>)).unwrap();
+    assert!(format!("{:?}", wrapper.inner).starts_with("Inner"));
+
+    // 没有 `struct` 时 `ident as ...` 按字面量匹配
+    define!(Cast: #(value: Ident) #(cast?: value as #(ty: Type)));
+    let cast: Cast = parse2(quote!(x value as u8)).unwrap();
+    assert_eq!(cast.value, "x");
+    assert!(cast.cast.is_some());
+    assert!(parse2::<Cast>(quote!(x)).unwrap().cast.is_none());
+    assert!(parse2::<Cast>(quote!(x other as u8)).is_err());
+}
+
+define!(
+    #[derive(Debug)]
+    pub Documented:
+    #(
+        /// 函数名
+        name: Ident
+    )
+    #(
+        /// 返回类型
+        #[allow(dead_code)]
+        ret?: -> #(ty: Type)
+    )
+    #(kind: Kind {
+        Fn: fn #(
+            /// 变体中的字段
+            #[allow(dead_code)]
+            body: Block
+        ),
+        Ident,
+    })
+);
+
+#[test]
+fn test_capture_attrs() {
+    use vacro_parser::grammar::Node;
+
+    let doc: Documented = parse2(quote!(foo -> u8 fn {})).unwrap();
+    assert_eq!(doc.name, "foo");
+    assert!(doc.ret.is_some());
+    assert!(matches!(doc.kind, Kind::Fn { .. }));
+
+    // 文档注释同时记录在语法描述中
+    let grammar = Documented::grammar();
+    assert!(matches!(
+        &grammar.items[0],
+        Node::Capture { doc: Some(doc), .. } if doc == "函数名"
+    ));
+    assert!(matches!(
+        &grammar.items[1],
+        Node::Optional { items } if matches!(
+            &items[0],
+            Node::Nested { doc: Some(doc), .. } if doc == "返回类型"
+        )
+    ));
+    // 没有文档注释的捕获保持不变，EBNF 中不包含文档
+    assert!(matches!(&grammar.items[2], Node::Choice { .. }));
+    assert_eq!(
+        grammar.to_ebnf(),
+        "Documented ::= Ident ( '->' Type )? Kind\nKind ::= 'fn' Block | Ident"
+    );
+    assert!(Documented::grammar()
+        .to_railroad_svg()
+        .contains("<title>函数名</title>"));
+}
+
+define!(Gated:
+    #(name: Ident)
+    #(#[cfg(any())] extra: LitInt)
+    #(#[cfg(all())] kept?: Token![,])
+    #(mode: Mode {
+        Pair: pair #(#[cfg(any())] left: Ident) #(right: Ident),
+        Single: single #(#[cfg(any())] only: Ident),
+    })
+);
+
+#[test]
+fn test_capture_cfg() {
+    // 被 `#[cfg]` 禁用的字段不会被解析，也不会出现在结构体中
+    let gated: Gated = parse2(quote!(a, pair b)).unwrap();
+    assert_eq!(gated.name, "a");
+    assert!(gated.kept.is_some());
+    assert!(matches!(&gated.mode, Mode::Pair { right } if right == "b"));
+    assert!(parse2::<Gated>(quote!(a 1, pair b)).is_err());
+    assert!(matches!(
+        parse2::<Gated>(quote!(a single)).unwrap().mode,
+        Mode::Single {}
+    ));
+
+    assert_round_trip::<Gated>(quote!(a, pair b));
+    assert_round_trip::<Gated>(quote!(a single));
+}
@@ -0,0 +1,77 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Ident, LitInt, Result, Type};
+use vacro_parser::match_tokens;
+
+fn describe(input: TokenStream) -> Result<String> {
+    match_tokens!(input {
+        fn #(name: Ident) => format!("fn {name}"),
+        struct #(name: Ident) { #(fields*[,]: #(field: Ident): #(ty: Type)) } => {
+            let fields = fields.iter().map(|f| f.field.to_string()).collect::<Vec<_>>();
+            format!("struct {name} {{ {} }}", fields.join(", "))
+        }
+        _ => String::from("unknown"),
+    })
+}
+
+// 测试按顺序选择第一个完整匹配的分支，捕获作为局部变量绑定
+#[test]
+fn test_match_arms() {
+    assert_eq!(describe(quote!(fn main)).unwrap(), "fn main");
+    assert_eq!(
+        describe(quote!(
+            struct Point {
+                x: i32,
+                y: i32,
+            }
+        ))
+        .unwrap(),
+        "struct Point { x, y }"
+    );
+    // 分支需要消费全部输入
+    assert_eq!(describe(quote!(fn main ())).unwrap(), "unknown");
+    assert_eq!(describe(quote!(mod m)).unwrap(), "unknown");
+}
+
+// 测试行内捕获与首个匹配优先
+#[test]
+fn test_match_first_wins() -> Result<()> {
+    let input = quote!(1 2);
+    let res = match_tokens!(input.clone() {
+        #(@: LitInt) #(@: LitInt) => _0.base10_parse::<u32>()? + _1.base10_parse::<u32>()?,
+        #(a: LitInt) #(b: LitInt) => a.base10_parse::<u32>()? * b.base10_parse::<u32>()?,
+    });
+    assert_eq!(res?, 3);
+    Ok(())
+}
+
+// 测试没有分支匹配时合并各分支的错误
+#[test]
+fn test_match_combined_error() {
+    let input = quote!(let x);
+    let res = match_tokens!(input {
+        fn #(name: Ident) => name,
+        struct #(name: Ident) => name,
+    });
+    let messages = res
+        .unwrap_err()
+        .into_iter()
+        .map(|e| e.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(messages, ["expected `fn`", "expected `struct`"]);
+}
+
+// 测试分支体中的 `?` 与外部变量不受生成代码影响
+#[test]
+fn test_match_body_scope() -> Result<()> {
+    let input = quote!(42);
+    let errors = "outer";
+    let value = match_tokens!(input {
+        #(n: LitInt) => {
+            let _ = errors;
+            n.base10_parse::<u32>()?
+        }
+    })?;
+    assert_eq!(value, 42);
+    Ok(())
+}
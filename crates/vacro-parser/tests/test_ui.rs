@@ -0,0 +1,5 @@
+#[test]
+fn ui_test() {
+    let test = trybuild::TestCases::new();
+    test.compile_fail("tests/ui/grammar_lint.rs");
+}
@@ -0,0 +1,22 @@
+#![deny(deprecated)]
+
+use syn::{Expr, Ident, LitInt, LitStr};
+use vacro_parser::define;
+
+define!(Ambiguous:
+    #(prefix?: Ident) #(name: Ident)
+);
+
+define!(Shadowed:
+    #(item: Kind {
+        Value: Expr,
+        Name: Ident,
+        Text: LitStr,
+    })
+);
+
+define!(EmptyRepeat:
+    #(items*: #(name?: Ident) #(values*: LitInt))
+);
+
+fn main() {}
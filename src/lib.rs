@@ -62,7 +62,7 @@ pub mod trace {
 ///
 pub mod prelude {
     #[cfg(feature = "parser")]
-    pub use crate::parser::{bind, define};
+    pub use crate::parser::{bind, define, match_tokens};
 
     #[cfg(feature = "report")]
     pub use crate::report::scope as report_scope;
@@ -76,7 +76,7 @@ pub mod prelude {
 
 // Re-export specific macros at root level for backward compatibility or ease of use
 #[cfg(feature = "parser")]
-pub use parser::{bind, define};
+pub use parser::{bind, define, match_tokens};
 
 #[cfg(feature = "trace")]
 pub use trace::snapshot;